@@ -0,0 +1,314 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Derive macros for `rustkdb`.
+//!
+//! The macros are re-exported by the main crate behind the `derive` cargo
+//! feature; depend on `rustkdb` with that feature instead of using this
+//! crate directly.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% Record %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// One struct field together with its `#[q(...)]` attributes.
+struct RecordField {
+  /// Field name in the struct.
+  ident: syn::Ident,
+  /// Field type in the struct.
+  ty: syn::Type,
+  /// Column name in q: the `rename` attribute or the field name.
+  column: String,
+  /// q type the field is reinterpreted as, from the `qtype` attribute.
+  qtype: Option<String>,
+}
+
+/// q types accepted in a `#[q(qtype = "...")]` attribute: those whose raw
+///  storage is shared with a plainer type, so the field can stay a plain
+///  Rust primitive.
+const KNOWN_QTYPES: &[&str] = &[
+  "symbol",
+  "timestamp",
+  "month",
+  "date",
+  "datetime",
+  "timespan",
+  "minute",
+  "second",
+  "time",
+];
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Map a struct with named fields to a q dictionary and `Vec`s of it to a
+///  q table, and back, by implementing the `IntoQ` and `FromQ` traits of
+///  `rustkdb`.
+///
+/// Field attributes:
+/// - `#[q(rename = "name")]`: column name in q when it differs from the
+///   field name.
+/// - `#[q(qtype = "type")]`: reinterpret the raw field value as this q
+///   type, e.g. an `i64` field as a `timestamp` or a `String` field as a
+///   `symbol`. Accepted types: `symbol`, `timestamp`, `month`, `date`,
+///   `datetime`, `timespan`, `minute`, `second`, `time`.
+#[proc_macro_derive(QRecord, attributes(q))]
+pub fn derive_q_record(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  match expand_q_record(input) {
+    Ok(expanded) => expanded.into(),
+    Err(error) => error.to_compile_error().into(),
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Generate the `IntoQ` and `FromQ` implementations for one struct.
+fn expand_q_record(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+  let name = &input.ident;
+  let fields = record_fields(&input)?;
+  let record_name = name.to_string();
+
+  let columns: Vec<&String> = fields.iter().map(|field| &field.column).collect();
+  let into_values = fields.iter().map(|field| {
+    let ident = &field.ident;
+    let conversion = quote! { ::rustkdb::convert::IntoQ::into_q(self.#ident) };
+    retyped(conversion, &field.qtype)
+  });
+  let column_buffers: Vec<syn::Ident> = fields
+    .iter()
+    .enumerate()
+    .map(|(index, _)| quote::format_ident!("column_{}", index))
+    .collect();
+  let push_fields = fields.iter().zip(&column_buffers).map(|(field, buffer)| {
+    let ident = &field.ident;
+    quote! { #buffer.push(row.#ident); }
+  });
+  let into_columns = fields.iter().zip(&column_buffers).map(|(field, buffer)| {
+    let conversion = quote! { ::rustkdb::convert::IntoQ::vec_into_q(#buffer) };
+    retyped(conversion, &field.qtype)
+  });
+
+  let from_fields = fields.iter().map(|field| {
+    let ident = &field.ident;
+    let column = &field.column;
+    let taken = retyped_from(quote! { take(#column)? }, &field.qtype);
+    quote! { #ident: ::rustkdb::convert::FromQ::from_q(#taken)? }
+  });
+  let column_reads = fields.iter().zip(&column_buffers).map(|(field, buffer)| {
+    let ty = &field.ty;
+    let column = &field.column;
+    let taken = retyped_from(quote! { take(#column)? }, &field.qtype);
+    quote! {
+      let #buffer = <::std::vec::Vec<#ty> as ::rustkdb::convert::FromQ>::from_q(#taken)?;
+    }
+  });
+  let first_buffer = &column_buffers[0];
+  let length_checks = column_buffers.iter().skip(1).map(|buffer| {
+    quote! {
+      if #buffer.len() != length {
+        return Err(::std::io::Error::new(
+          ::std::io::ErrorKind::InvalidData,
+          format!("ragged columns in a {} table", #record_name),
+        ));
+      }
+    }
+  });
+  let row_iterators: Vec<syn::Ident> = column_buffers
+    .iter()
+    .map(|buffer| quote::format_ident!("iter_{}", buffer))
+    .collect();
+  let make_iterators = column_buffers.iter().zip(&row_iterators).map(|(buffer, iterator)| {
+    quote! { let mut #iterator = #buffer.into_iter(); }
+  });
+  let row_fields = fields.iter().zip(&row_iterators).map(|(field, iterator)| {
+    let ident = &field.ident;
+    quote! { #ident: #iterator.next().expect("length checked above") }
+  });
+
+  Ok(quote! {
+    impl ::rustkdb::convert::IntoQ for #name {
+      fn into_q(self) -> ::rustkdb::qtype::Q {
+        let keys = ::rustkdb::qtype::Q::SymbolList(::rustkdb::qtype::QList::new(vec![
+          #(#columns.to_string()),*
+        ]));
+        let values = ::rustkdb::qtype::Q::MixedList(vec![#(#into_values),*]);
+        ::rustkdb::qtype::Q::Dictionary(::rustkdb::qtype::QDictionary::new(keys, values))
+      }
+
+      fn vec_into_q(values: ::std::vec::Vec<Self>) -> ::rustkdb::qtype::Q {
+        #(let mut #column_buffers = ::std::vec::Vec::with_capacity(values.len());)*
+        for row in values {
+          #(#push_fields)*
+        }
+        let columns = vec![#(#columns.to_string()),*];
+        let column_values = vec![#(#into_columns),*];
+        ::rustkdb::qtype::Q::Table(
+          ::rustkdb::qtype::QTable::new(columns, column_values)
+            .expect("one value list per column"),
+        )
+      }
+    }
+
+    impl ::rustkdb::convert::FromQ for #name {
+      fn from_q(object: ::rustkdb::qtype::Q) -> ::std::io::Result<Self> {
+        let dictionary = match object {
+          ::rustkdb::qtype::Q::Dictionary(dictionary) => dictionary,
+          _ => {
+            return Err(::std::io::Error::new(
+              ::std::io::ErrorKind::InvalidData,
+              format!("a {} record converts only from a dictionary", #record_name),
+            ));
+          }
+        };
+        let (keys, values) = dictionary.into_parts();
+        let keys = <::std::vec::Vec<::std::string::String> as ::rustkdb::convert::FromQ>::from_q(keys)?;
+        let mut values = ::rustkdb::convert::q_list_items(values)?;
+        let mut take = |name: &str| -> ::std::io::Result<::rustkdb::qtype::Q> {
+          match keys.iter().position(|key| key == name) {
+            Some(index) if index < values.len() => {
+              Ok(::std::mem::replace(&mut values[index], ::rustkdb::qtype::Q::Null))
+            }
+            _ => Err(::std::io::Error::new(
+              ::std::io::ErrorKind::InvalidData,
+              format!("missing key '{}' for a {} record", name, #record_name),
+            )),
+          }
+        };
+        Ok(#name { #(#from_fields),* })
+      }
+
+      fn vec_from_q(object: ::rustkdb::qtype::Q) -> ::std::io::Result<::std::vec::Vec<Self>> {
+        let table = match object {
+          ::rustkdb::qtype::Q::Table(table) => table,
+          // Anything else falls back to item-wise conversion, e.g. a mixed
+          //  list of dictionaries.
+          other => {
+            return ::rustkdb::convert::q_list_items(other)?
+              .into_iter()
+              .map(<Self as ::rustkdb::convert::FromQ>::from_q)
+              .collect();
+          }
+        };
+        let (keys, mut values) = table.into_parts();
+        let mut take = |name: &str| -> ::std::io::Result<::rustkdb::qtype::Q> {
+          match keys.iter().position(|key| key == name) {
+            Some(index) if index < values.len() => {
+              Ok(::std::mem::replace(&mut values[index], ::rustkdb::qtype::Q::Null))
+            }
+            _ => Err(::std::io::Error::new(
+              ::std::io::ErrorKind::InvalidData,
+              format!("missing column '{}' for a {} table", name, #record_name),
+            )),
+          }
+        };
+        #(#column_reads)*
+        let length = #first_buffer.len();
+        #(#length_checks)*
+        #(#make_iterators)*
+        let mut rows = ::std::vec::Vec::with_capacity(length);
+        for _ in 0..length {
+          rows.push(#name { #(#row_fields),* });
+        }
+        Ok(rows)
+      }
+    }
+  })
+}
+
+/// Collect the named fields of the struct with their attributes.
+fn record_fields(input: &DeriveInput) -> syn::Result<Vec<RecordField>> {
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => &fields.named,
+      _ => {
+        return Err(syn::Error::new_spanned(
+          &input.ident,
+          "QRecord requires named fields",
+        ));
+      }
+    },
+    _ => {
+      return Err(syn::Error::new_spanned(
+        &input.ident,
+        "QRecord can only be derived for structs",
+      ));
+    }
+  };
+  if fields.is_empty() {
+    return Err(syn::Error::new_spanned(
+      &input.ident,
+      "QRecord requires at least one field",
+    ));
+  }
+  fields
+    .iter()
+    .map(|field| {
+      let ident = field.ident.clone().expect("named fields checked above");
+      let mut column = ident.to_string();
+      let mut qtype = None;
+      for attribute in &field.attrs {
+        if !attribute.path().is_ident("q") {
+          continue;
+        }
+        attribute.parse_nested_meta(|meta| {
+          let value: syn::LitStr = meta.value()?.parse()?;
+          if meta.path.is_ident("rename") {
+            column = value.value();
+            Ok(())
+          } else if meta.path.is_ident("qtype") {
+            if !KNOWN_QTYPES.contains(&value.value().as_str()) {
+              return Err(meta.error(format!("unknown q type '{}'", value.value())));
+            }
+            qtype = Some(value.value());
+            Ok(())
+          } else {
+            Err(meta.error("expected `rename` or `qtype`"))
+          }
+        })?;
+      }
+      Ok(RecordField {
+        ident,
+        ty: field.ty.clone(),
+        column,
+        qtype,
+      })
+    })
+    .collect()
+}
+
+/// Wrap a conversion in a reinterpretation to the attributed q type.
+fn retyped(
+  conversion: proc_macro2::TokenStream,
+  qtype: &Option<String>,
+) -> proc_macro2::TokenStream {
+  match qtype {
+    Some(qtype) => quote! { ::rustkdb::convert::retype_into_q(#conversion, #qtype) },
+    None => conversion,
+  }
+}
+
+/// Wrap an extraction in a reinterpretation back to the raw storage type.
+fn retyped_from(
+  extraction: proc_macro2::TokenStream,
+  qtype: &Option<String>,
+) -> proc_macro2::TokenStream {
+  match qtype {
+    Some(qtype) => quote! { ::rustkdb::convert::retype_from_q(#extraction, #qtype) },
+    None => extraction,
+  }
+}
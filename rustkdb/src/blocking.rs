@@ -0,0 +1,253 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Blocking synchronous client without an async runtime.
+//!
+//! The module mirrors the query API of [`connection`](crate::connection)
+//! over `std::net` sockets, for CLI tools and scripts where pulling in
+//! Tokio is not worth it. Wire format, handshake and error reporting are
+//! identical to the async client; only scheduling differs.
+//!
+//! Enabled with the `blocking` cargo feature.
+//!
+//! # Example
+//! ```no_run
+//! use rustkdb::blocking::connect;
+//! use rustkdb::qtype::Q;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut handle = connect("localhost", 5000, "kdbuser:pass", 200, 0)?;
+//! let result = handle.send_string_query("6*7")?;
+//! assert_eq!(result, Q::Long(42));
+//! # Ok(())}
+//! ```
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use crate::deserialization::{decompress, deserialize_q};
+use crate::qtype::Q;
+use crate::serialization::{
+  serialize_message, serialize_string_query, MSG_TYPE_ASYNC, MSG_TYPE_RESPONSE, MSG_TYPE_SYNC,
+};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% Handle %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Blocking handle to a q/kdb+ process, obtained from [`connect`].
+pub struct Handle {
+  /// The underlying socket.
+  stream: TcpStream,
+}
+
+impl Handle {
+  /// Send a string query synchronously and wait for the result.
+  /// # Parameters
+  /// - `query`: Query in the form of string, e.g. `"6*7"`.
+  pub fn send_string_query(&mut self, query: &str) -> io::Result<Q> {
+    let message = serialize_string_query(query, MSG_TYPE_SYNC);
+    self.stream.write_all(&message)?;
+    self.receive_response()
+  }
+
+  /// Send a string query asynchronously, i.e. without a response.
+  pub fn send_string_query_async(&mut self, query: &str) -> io::Result<()> {
+    let message = serialize_string_query(query, MSG_TYPE_ASYNC);
+    self.stream.write_all(&message)
+  }
+
+  /// Send a q object synchronously and wait for the result.
+  pub fn send_query(&mut self, query: Q) -> io::Result<Q> {
+    let message = serialize_message(&query, MSG_TYPE_SYNC);
+    self.stream.write_all(&message)?;
+    self.receive_response()
+  }
+
+  /// Send a q object asynchronously, i.e. without a response.
+  pub fn send_query_async(&mut self, query: Q) -> io::Result<()> {
+    let message = serialize_message(&query, MSG_TYPE_ASYNC);
+    self.stream.write_all(&message)
+  }
+
+  /// Close the connection, flushing buffered writes.
+  pub fn close(self) -> io::Result<()> {
+    self.stream.shutdown(std::net::Shutdown::Both)
+  }
+
+  /// Read messages until a response arrives. Asynchronous messages pushed
+  ///  by the remote process in the meantime are discarded — the blocking
+  ///  client has no subscriber surface.
+  fn receive_response(&mut self) -> io::Result<Q> {
+    loop {
+      let mut header = [0u8; 8];
+      self.stream.read_exact(&mut header)?;
+      let little_endian = header[0] == 1;
+      let compressed = header[2] == 1;
+      let size_bytes: [u8; 4] = header[4..8].try_into().unwrap();
+      let total_size = if little_endian {
+        u32::from_le_bytes(size_bytes)
+      } else {
+        u32::from_be_bytes(size_bytes)
+      } as usize;
+      if total_size < 8 {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidData,
+          "broken message: header declares impossible size",
+        ));
+      }
+      let mut body = vec![0u8; total_size - 8];
+      self.stream.read_exact(&mut body)?;
+      if compressed {
+        body = decompress(&body, little_endian)?;
+      }
+      if header[1] == MSG_TYPE_RESPONSE {
+        return deserialize_q(&body, little_endian);
+      }
+    }
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Connect to a q/kdb+ process over TCP without an async runtime.
+/// # Parameters
+/// - `host`: Hostname or address of the target q process.
+/// - `port`: Port of the target q process.
+/// - `credential`: Credential in the form of `user:password`.
+/// - `timeout_millis`: Timeout of each connection attempt in milliseconds.
+///   0 means no timeout.
+/// - `retry_interval_millis`: Interval between connection attempts in
+///   milliseconds. 0 means a single attempt.
+pub fn connect(
+  host: &str,
+  port: u16,
+  credential: &str,
+  timeout_millis: u64,
+  retry_interval_millis: u64,
+) -> io::Result<Handle> {
+  let attempts = if retry_interval_millis == 0 {
+    1
+  } else {
+    crate::connection::MAX_CONNECT_ATTEMPTS
+  };
+  let mut last_error = None;
+  for i in 0..attempts {
+    if i != 0 {
+      std::thread::sleep(Duration::from_millis(retry_interval_millis));
+    }
+    match open_tcp(host, port, timeout_millis).and_then(|stream| handshake(stream, credential)) {
+      Ok(handle) => return Ok(handle),
+      Err(error) => last_error = Some(error),
+    }
+  }
+  Err(last_error.expect("at least one connection attempt"))
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Open a TCP connection, honoring the attempt timeout.
+fn open_tcp(host: &str, port: u16, timeout_millis: u64) -> io::Result<TcpStream> {
+  use std::net::ToSocketAddrs;
+  let mut last_error = None;
+  for address in (host, port).to_socket_addrs()? {
+    let attempt = if timeout_millis == 0 {
+      TcpStream::connect(address)
+    } else {
+      TcpStream::connect_timeout(&address, Duration::from_millis(timeout_millis))
+    };
+    match attempt {
+      Ok(stream) => {
+        stream.set_nodelay(true)?;
+        return Ok(stream);
+      }
+      Err(error) => last_error = Some(error),
+    }
+  }
+  Err(last_error.unwrap_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("no address found for {}:{}", host, port),
+    )
+  }))
+}
+
+/// Perform the kdb+ handshake, with the same diagnostics as the async
+///  client.
+fn handshake(mut stream: TcpStream, credential: &str) -> io::Result<Handle> {
+  let mut message = Vec::with_capacity(credential.len() + 2);
+  message.extend_from_slice(credential.as_bytes());
+  message.push(crate::connection::CAPABILITY);
+  message.push(0);
+  stream.write_all(&message)?;
+  let mut capability = [0u8; 1];
+  match stream.read_exact(&mut capability) {
+    Ok(_) => {}
+    // kdb+ simply closes the connection when `-u`/`-U` or `.z.pw` rejects
+    //  the credential, so an immediate end of stream means access denied.
+    Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+      return Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "handshake failed: access denied (credential rejected by -u/-U or .z.pw)",
+      ));
+    }
+    Err(error) => {
+      return Err(io::Error::new(
+        error.kind(),
+        format!("handshake failed: {}", error),
+      ));
+    }
+  }
+  if capability[0] > crate::connection::CAPABILITY {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      format!(
+        "handshake failed: remote answered IPC version {} but this client supports at most {}",
+        capability[0],
+        crate::connection::CAPABILITY
+      ),
+    ));
+  }
+  Ok(Handle { stream })
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::MockServer;
+
+  #[test]
+  fn blocking_query_against_the_mock_server() {
+    // The mock server needs a runtime; the client under test does not.
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+      .enable_all()
+      .build()
+      .unwrap();
+    let server = runtime.block_on(async {
+      MockServer::builder()
+        .respond("6*7", Q::Long(42))
+        .start()
+        .await
+        .unwrap()
+    });
+    let mut handle = connect("127.0.0.1", server.port(), "kdbuser:pass", 1000, 0).unwrap();
+    assert_eq!(handle.send_string_query("6*7").unwrap(), Q::Long(42));
+    handle.close().unwrap();
+  }
+}
@@ -0,0 +1,163 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Named global client registry.
+//!
+//! Applications talking to several q processes — an RDB, an HDB, a
+//! gateway — usually configure them once at startup and use them all over
+//! the code base. [`register`] stores the [`ConnectOptions`] of each
+//! process under a logical name; [`get`] hands out a [`SharedHandle`] for
+//! that name, connecting lazily on first use and reconnecting when the
+//! previous connection has broken, so application code never threads
+//! handles through every function.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Mutex, OnceLock};
+
+use crate::connection::{ConnectOptions, SharedHandle};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Registered clients by logical name.
+#[derive(Default)]
+struct Registry {
+  /// Connection options of every registered name.
+  options: HashMap<String, ConnectOptions>,
+  /// Live handles, created on first use.
+  handles: HashMap<String, SharedHandle>,
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// The process-wide registry.
+fn registry() -> &'static Mutex<Registry> {
+  static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+  REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Register connection options under a logical name, replacing any earlier
+///  registration. No connection is made until the first [`get`]; a live
+///  handle of an earlier registration is dropped.
+/// # Example
+/// ```no_run
+/// # use rustkdb::clients;
+/// # use rustkdb::connection::ConnectOptions;
+/// clients::register(
+///   "rdb",
+///   ConnectOptions::new().port(5010).credential("kdbuser:pass"),
+/// );
+/// ```
+pub fn register(name: &str, options: ConnectOptions) {
+  let mut registry = registry().lock().unwrap();
+  registry.options.insert(name.to_string(), options);
+  registry.handles.remove(name);
+}
+
+/// Remove a registration and drop its live handle, returning `true` when
+///  the name was registered.
+pub fn unregister(name: &str) -> bool {
+  let mut registry = registry().lock().unwrap();
+  registry.handles.remove(name);
+  registry.options.remove(name).is_some()
+}
+
+/// Obtain the shared handle registered under a logical name, connecting on
+///  first use. A handle whose connection has broken since is replaced by a
+///  freshly connected one, so callers always receive a usable handle or a
+///  connection error. Fails with an error of kind `NotFound` when the name
+///  was never registered.
+/// # Example
+/// ```no_run
+/// # use rustkdb::clients;
+/// # #[tokio::main] async fn main() -> std::io::Result<()> {
+/// let rdb = clients::get("rdb").await?;
+/// let count = rdb.send_string_query("count trade").await?;
+/// # Ok(())}
+/// ```
+pub async fn get(name: &str) -> io::Result<SharedHandle> {
+  // Connect outside the lock; concurrent first uses may race, in which
+  //  case the last connection wins and the others serve their caller only.
+  let (options, cached) = {
+    let registry = registry().lock().unwrap();
+    match registry.options.get(name) {
+      Some(options) => (options.clone(), registry.handles.get(name).cloned()),
+      None => {
+        return Err(io::Error::new(
+          io::ErrorKind::NotFound,
+          format!("no client registered under '{}'", name),
+        ));
+      }
+    }
+  };
+  if let Some(handle) = cached {
+    if handle.is_connected() {
+      return Ok(handle);
+    }
+  }
+  let handle = SharedHandle::spawn(options.connect().await?);
+  registry()
+    .lock()
+    .unwrap()
+    .handles
+    .insert(name.to_string(), handle.clone());
+  Ok(handle)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::qtype::Q;
+
+  /// Resolving a name connects lazily and reuses the live handle.
+  #[tokio::test]
+  async fn names_resolve_to_lazily_connected_handles() {
+    let server = crate::testing::MockServer::builder()
+      .respond("count trade", Q::Long(3))
+      .start()
+      .await
+      .unwrap();
+    register(
+      "clients-test-rdb",
+      ConnectOptions::new()
+        .host("127.0.0.1")
+        .port(server.port())
+        .credential("kdbuser:pass"),
+    );
+    let first = get("clients-test-rdb").await.unwrap();
+    assert_eq!(
+      first.send_string_query("count trade").await.unwrap(),
+      Q::Long(3)
+    );
+    // The cached handle is reused while it is alive.
+    let second = get("clients-test-rdb").await.unwrap();
+    assert_eq!(
+      second.send_string_query("count trade").await.unwrap(),
+      Q::Long(3)
+    );
+    assert!(unregister("clients-test-rdb"));
+    assert!(!unregister("clients-test-rdb"));
+    let error = match get("clients-test-rdb").await {
+      Err(error) => error,
+      Ok(_) => panic!("an unregistered name must not resolve"),
+    };
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+  }
+}
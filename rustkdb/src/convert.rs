@@ -0,0 +1,1003 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Conversions between [`Q`] objects and plain Rust types.
+//!
+//! [`FromQ`] turns a query response into the Rust type the caller actually
+//! wants — primitives, `String`, `Vec<T>`, `Option<T>` and tuples — with an
+//! error of kind `InvalidData` naming both sides on a type mismatch. The
+//! typed query methods
+//! [`send_string_query_as`](crate::connection::Handle::send_string_query_as)
+//! and [`send_query_as`](crate::connection::Handle::send_query_as) apply the
+//! conversion directly to the response, eliminating chains of manual
+//! `match` arms at every call site.
+//!
+//! [`IntoQ`] is the opposite direction: plain Rust values — and, with the
+//! `chrono` cargo feature, calendar types — convert into [`Q`] arguments,
+//! so a functional query is written as
+//! `query!("insert", "trade", rows)` instead of wrapping every argument in
+//! a [`Q`] constructor by hand.
+//!
+//! # Example
+//! ```no_run
+//! use rustkdb::connection::connect;
+//!
+//! # #[tokio::main] async fn main() -> std::io::Result<()> {
+//! let mut handle = connect("localhost", 5000, "kdbuser:pass", 1000, 0).await?;
+//! let count = handle.send_string_query_as::<i64>("count trade").await?;
+//! let syms = handle
+//!   .send_string_query_as::<Vec<String>>("exec distinct sym from trade")
+//!   .await?;
+//! # Ok(())}
+//! ```
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::io;
+
+use crate::qtype::{Q, QList};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Traits                         //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% FromQ %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Conversion out of a [`Q`] object.
+///
+/// Conversions are strict: an atom converts only into its matching Rust
+/// type, so a q `int` does not silently widen into `i64`. Lists convert
+/// into `Vec<T>` element by element, mixed lists of known length into
+/// tuples, and the general null `(::)` into `None` of any `Option<T>`.
+pub trait FromQ: Sized {
+  /// Convert the object, failing with an error of kind `InvalidData` when
+  ///  it does not represent `Self`.
+  fn from_q(object: Q) -> io::Result<Self>;
+
+  /// How a `Vec<Self>` converts. `#[derive(QRecord)]` types override this
+  ///  to read a whole table column-wise.
+  fn vec_from_q(object: Q) -> io::Result<Vec<Self>> {
+    list_items(object)?.into_iter().map(Self::from_q).collect()
+  }
+}
+
+//%% IntoQ %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Conversion into a [`Q`] object.
+///
+/// `&str` converts into a symbol — the form function and table names take
+/// in a functional query — while an owned `String` converts into a char
+/// list, i.e. q string data. A `Vec` of a simple type becomes the matching
+/// simple list (`Vec<i64>` → long list), any other `Vec` and all tuples
+/// become mixed lists, and with the `chrono` cargo feature the calendar
+/// types of that crate become the corresponding q temporal atoms.
+pub trait IntoQ {
+  /// Convert the value.
+  fn into_q(self) -> Q;
+
+  /// How a homogeneous `Vec` of this type converts. Simple types override
+  ///  this to produce the matching simple list instead of a mixed list.
+  fn vec_into_q(values: Vec<Self>) -> Q
+  where
+    Self: Sized,
+  {
+    Q::MixedList(values.into_iter().map(IntoQ::into_q).collect())
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                 Trait Implementations                 //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+impl FromQ for Q {
+  fn from_q(object: Q) -> io::Result<Self> {
+    Ok(object)
+  }
+}
+
+impl FromQ for bool {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Bool(value) => Ok(value),
+      other => Err(mismatch(&other, "bool")),
+    }
+  }
+}
+
+impl FromQ for [u8; 16] {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Guid(value) => Ok(value),
+      other => Err(mismatch(&other, "[u8; 16]")),
+    }
+  }
+}
+
+impl FromQ for u8 {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Byte(value) => Ok(value),
+      other => Err(mismatch(&other, "u8")),
+    }
+  }
+}
+
+impl FromQ for i16 {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Short(value) => Ok(value),
+      other => Err(mismatch(&other, "i16")),
+    }
+  }
+}
+
+impl FromQ for i32 {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Int(value) => Ok(value),
+      other => Err(mismatch(&other, "i32")),
+    }
+  }
+}
+
+impl FromQ for i64 {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Long(value) => Ok(value),
+      other => Err(mismatch(&other, "i64")),
+    }
+  }
+}
+
+impl FromQ for f32 {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Real(value) => Ok(value),
+      other => Err(mismatch(&other, "f32")),
+    }
+  }
+}
+
+impl FromQ for f64 {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Float(value) => Ok(value),
+      other => Err(mismatch(&other, "f64")),
+    }
+  }
+}
+
+impl FromQ for char {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Char(value) => Ok(value),
+      other => Err(mismatch(&other, "char")),
+    }
+  }
+}
+
+impl FromQ for String {
+  /// Both char lists and symbols convert into `String`, as q answers
+  ///  textual queries with either depending on the expression.
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::String(value) => Ok(value),
+      Q::Symbol(value) => Ok(value),
+      other => Err(mismatch(&other, "String")),
+    }
+  }
+}
+
+impl<T: FromQ> FromQ for Option<T> {
+  fn from_q(object: Q) -> io::Result<Self> {
+    match object {
+      Q::Null => Ok(None),
+      other => T::from_q(other).map(Some),
+    }
+  }
+}
+
+impl<T: FromQ> FromQ for Vec<T> {
+  fn from_q(object: Q) -> io::Result<Self> {
+    T::vec_from_q(object)
+  }
+}
+
+/// Tuples convert from mixed lists of the matching length, element by
+///  element. The repetition across arities is mechanical, hence a macro.
+macro_rules! impl_from_q_for_tuple {
+  ($($name:ident),+) => {
+    impl<$($name: FromQ),+> FromQ for ($($name,)+) {
+      fn from_q(object: Q) -> io::Result<Self> {
+        let arity = [$(stringify!($name)),+].len();
+        let items = list_items(object)?;
+        if items.len() != arity {
+          return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+              "cannot convert a list of {} items into a tuple of {}",
+              items.len(),
+              arity
+            ),
+          ));
+        }
+        let mut items = items.into_iter();
+        Ok(($($name::from_q(items.next().expect("length checked above"))?,)+))
+      }
+    }
+  };
+}
+
+impl_from_q_for_tuple!(A, B);
+impl_from_q_for_tuple!(A, B, C);
+impl_from_q_for_tuple!(A, B, C, D);
+impl_from_q_for_tuple!(A, B, C, D, E);
+impl_from_q_for_tuple!(A, B, C, D, E, F);
+impl_from_q_for_tuple!(A, B, C, D, E, F, G);
+impl_from_q_for_tuple!(A, B, C, D, E, F, G, H);
+
+impl IntoQ for Q {
+  fn into_q(self) -> Q {
+    self
+  }
+}
+
+impl IntoQ for bool {
+  fn into_q(self) -> Q {
+    Q::Bool(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::BoolList(QList::new(values))
+  }
+}
+
+impl IntoQ for [u8; 16] {
+  fn into_q(self) -> Q {
+    Q::Guid(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::GuidList(QList::new(values))
+  }
+}
+
+impl IntoQ for u8 {
+  fn into_q(self) -> Q {
+    Q::Byte(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::ByteList(QList::new(values))
+  }
+}
+
+impl IntoQ for i16 {
+  fn into_q(self) -> Q {
+    Q::Short(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::ShortList(QList::new(values))
+  }
+}
+
+impl IntoQ for i32 {
+  fn into_q(self) -> Q {
+    Q::Int(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::IntList(QList::new(values))
+  }
+}
+
+impl IntoQ for i64 {
+  fn into_q(self) -> Q {
+    Q::Long(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::LongList(QList::new(values))
+  }
+}
+
+impl IntoQ for f32 {
+  fn into_q(self) -> Q {
+    Q::Real(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::RealList(QList::new(values))
+  }
+}
+
+impl IntoQ for f64 {
+  fn into_q(self) -> Q {
+    Q::Float(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::FloatList(QList::new(values))
+  }
+}
+
+impl IntoQ for char {
+  fn into_q(self) -> Q {
+    Q::Char(self)
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::String(values.into_iter().collect())
+  }
+}
+
+impl IntoQ for &str {
+  fn into_q(self) -> Q {
+    Q::Symbol(self.to_string())
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::SymbolList(QList::new(
+      values.into_iter().map(str::to_string).collect(),
+    ))
+  }
+}
+
+impl IntoQ for String {
+  fn into_q(self) -> Q {
+    Q::String(self)
+  }
+}
+
+impl<T: IntoQ> IntoQ for Vec<T> {
+  fn into_q(self) -> Q {
+    T::vec_into_q(self)
+  }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoQ for chrono::DateTime<chrono::Utc> {
+  /// Timestamps beyond the q range saturate at the extreme representable
+  ///  nanosecond offsets.
+  fn into_q(self) -> Q {
+    Q::Timestamp(nanos_since_q_epoch(self.naive_utc()))
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::TimestampList(QList::new(
+      values
+        .into_iter()
+        .map(|value| nanos_since_q_epoch(value.naive_utc()))
+        .collect(),
+    ))
+  }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoQ for chrono::NaiveDateTime {
+  /// Naive datetimes are interpreted as UTC.
+  fn into_q(self) -> Q {
+    Q::Timestamp(nanos_since_q_epoch(self))
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::TimestampList(QList::new(
+      values.into_iter().map(nanos_since_q_epoch).collect(),
+    ))
+  }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoQ for chrono::NaiveDate {
+  fn into_q(self) -> Q {
+    Q::Date(days_since_q_epoch(self))
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::DateList(QList::new(
+      values.into_iter().map(days_since_q_epoch).collect(),
+    ))
+  }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoQ for chrono::NaiveTime {
+  fn into_q(self) -> Q {
+    Q::Time(millis_since_midnight(self))
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::TimeList(QList::new(
+      values.into_iter().map(millis_since_midnight).collect(),
+    ))
+  }
+}
+
+#[cfg(feature = "chrono")]
+impl IntoQ for chrono::Duration {
+  /// Durations beyond the q range saturate at the extreme representable
+  ///  nanosecond counts.
+  fn into_q(self) -> Q {
+    Q::Timespan(saturating_nanos(self))
+  }
+
+  fn vec_into_q(values: Vec<Self>) -> Q {
+    Q::TimespanList(QList::new(
+      values.into_iter().map(saturating_nanos).collect(),
+    ))
+  }
+}
+
+/// Tuples convert into mixed lists, element by element.
+macro_rules! impl_into_q_for_tuple {
+  ($($name:ident: $index:tt),+) => {
+    impl<$($name: IntoQ),+> IntoQ for ($($name,)+) {
+      fn into_q(self) -> Q {
+        Q::MixedList(vec![$(self.$index.into_q()),+])
+      }
+    }
+  };
+}
+
+impl_into_q_for_tuple!(A: 0, B: 1);
+impl_into_q_for_tuple!(A: 0, B: 1, C: 2);
+impl_into_q_for_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_into_q_for_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_into_q_for_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_into_q_for_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_into_q_for_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Macros                         //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Build the mixed list of a functional query from [`IntoQ`] arguments.
+///
+/// `&str` arguments become symbols and owned `String`s become char lists,
+/// as with [`IntoQ`] everywhere else.
+/// # Example
+/// ```
+/// use rustkdb::qtype::Q;
+///
+/// let call = rustkdb::query!("insert", "trade", vec![1i64, 2, 3]);
+/// assert_eq!(
+///   call,
+///   Q::MixedList(vec![
+///     Q::Symbol("insert".to_string()),
+///     Q::Symbol("trade".to_string()),
+///     Q::LongList(rustkdb::qtype::QList::new(vec![1, 2, 3])),
+///   ])
+/// );
+/// ```
+#[macro_export]
+macro_rules! query {
+  ($($argument:expr),+ $(,)?) => {
+    $crate::qtype::Q::MixedList(vec![
+      $($crate::convert::IntoQ::into_q($argument)),+
+    ])
+  };
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Break a list of any kind into its items. Exposed for the code expanded
+///  by `#[derive(QRecord)]`; not part of the public API surface.
+#[doc(hidden)]
+pub fn q_list_items(object: Q) -> io::Result<Vec<Q>> {
+  list_items(object)
+}
+
+/// Reinterpret a converted object as the q type named in a
+///  `#[q(qtype = "...")]` attribute, e.g. a long as a timestamp. Objects
+///  whose raw storage does not match pass through unchanged. Exposed for
+///  the code expanded by `#[derive(QRecord)]`.
+#[doc(hidden)]
+pub fn retype_into_q(object: Q, qtype: &str) -> Q {
+  match (qtype, object) {
+    ("symbol", Q::String(text)) => Q::Symbol(text),
+    ("symbol", Q::MixedList(items))
+      if items
+        .iter()
+        .all(|item| matches!(item, Q::String(_) | Q::Symbol(_))) =>
+    {
+      Q::SymbolList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::String(text) | Q::Symbol(text) => text,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    ("timestamp", Q::Long(value)) => Q::Timestamp(value),
+    ("timestamp", Q::LongList(list)) => Q::TimestampList(list),
+    ("timespan", Q::Long(value)) => Q::Timespan(value),
+    ("timespan", Q::LongList(list)) => Q::TimespanList(list),
+    ("month", Q::Int(value)) => Q::Month(value),
+    ("month", Q::IntList(list)) => Q::MonthList(list),
+    ("date", Q::Int(value)) => Q::Date(value),
+    ("date", Q::IntList(list)) => Q::DateList(list),
+    ("minute", Q::Int(value)) => Q::Minute(value),
+    ("minute", Q::IntList(list)) => Q::MinuteList(list),
+    ("second", Q::Int(value)) => Q::Second(value),
+    ("second", Q::IntList(list)) => Q::SecondList(list),
+    ("time", Q::Int(value)) => Q::Time(value),
+    ("time", Q::IntList(list)) => Q::TimeList(list),
+    ("datetime", Q::Float(value)) => Q::Datetime(value),
+    ("datetime", Q::FloatList(list)) => Q::DatetimeList(list),
+    (_, object) => object,
+  }
+}
+
+/// Reinterpret a temporal object back as its raw storage type, the inverse
+///  of [`retype_into_q`]. Exposed for the code expanded by
+///  `#[derive(QRecord)]`.
+#[doc(hidden)]
+pub fn retype_from_q(object: Q, qtype: &str) -> Q {
+  match (qtype, object) {
+    ("timestamp", Q::Timestamp(value)) => Q::Long(value),
+    ("timestamp", Q::TimestampList(list)) => Q::LongList(list),
+    ("timespan", Q::Timespan(value)) => Q::Long(value),
+    ("timespan", Q::TimespanList(list)) => Q::LongList(list),
+    ("month", Q::Month(value)) => Q::Int(value),
+    ("month", Q::MonthList(list)) => Q::IntList(list),
+    ("date", Q::Date(value)) => Q::Int(value),
+    ("date", Q::DateList(list)) => Q::IntList(list),
+    ("minute", Q::Minute(value)) => Q::Int(value),
+    ("minute", Q::MinuteList(list)) => Q::IntList(list),
+    ("second", Q::Second(value)) => Q::Int(value),
+    ("second", Q::SecondList(list)) => Q::IntList(list),
+    ("time", Q::Time(value)) => Q::Int(value),
+    ("time", Q::TimeList(list)) => Q::IntList(list),
+    ("datetime", Q::Datetime(value)) => Q::Float(value),
+    ("datetime", Q::DatetimeList(list)) => Q::FloatList(list),
+    (_, object) => object,
+  }
+}
+
+//%% Null-aware conversions %%//vvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+impl Q {
+  /// Convert a short atom, mapping the null `0Nh` to `None` instead of the
+  ///  sentinel `i16::MIN`.
+  pub fn into_i16_opt(self) -> io::Result<Option<i16>> {
+    match self {
+      Q::Short(value) => Ok((value != i16::MIN).then_some(value)),
+      other => Err(mismatch(&other, "Option<i16>")),
+    }
+  }
+
+  /// Convert an int atom, mapping the null `0Ni` to `None` instead of the
+  ///  sentinel `i32::MIN`.
+  pub fn into_i32_opt(self) -> io::Result<Option<i32>> {
+    match self {
+      Q::Int(value) => Ok((value != i32::MIN).then_some(value)),
+      other => Err(mismatch(&other, "Option<i32>")),
+    }
+  }
+
+  /// Convert a long atom, mapping the null `0N` to `None` instead of the
+  ///  sentinel `i64::MIN`.
+  pub fn into_i64_opt(self) -> io::Result<Option<i64>> {
+    match self {
+      Q::Long(value) => Ok((value != i64::MIN).then_some(value)),
+      other => Err(mismatch(&other, "Option<i64>")),
+    }
+  }
+
+  /// Convert a real atom, mapping the null `0Ne` to `None` instead of NaN.
+  pub fn into_f32_opt(self) -> io::Result<Option<f32>> {
+    match self {
+      Q::Real(value) => Ok((!value.is_nan()).then_some(value)),
+      other => Err(mismatch(&other, "Option<f32>")),
+    }
+  }
+
+  /// Convert a float atom, mapping the null `0n` to `None` instead of NaN.
+  pub fn into_f64_opt(self) -> io::Result<Option<f64>> {
+    match self {
+      Q::Float(value) => Ok((!value.is_nan()).then_some(value)),
+      other => Err(mismatch(&other, "Option<f64>")),
+    }
+  }
+
+  /// Convert a symbol or char list, mapping the null symbol `` ` `` to
+  ///  `None`. A char list is never null, so an empty string stays
+  ///  `Some("")`.
+  pub fn into_string_opt(self) -> io::Result<Option<String>> {
+    match self {
+      Q::Symbol(value) => Ok((!value.is_empty()).then_some(value)),
+      Q::String(value) => Ok(Some(value)),
+      other => Err(mismatch(&other, "Option<String>")),
+    }
+  }
+}
+
+/// List conversions mapping embedded q nulls to `None` per element. The
+///  repetition across list types is mechanical, hence a macro.
+macro_rules! impl_into_opt_vec {
+  ($($(#[doc = $doc:expr])+ $name:ident: $variant:ident => $type:ty, $is_null:expr, $target:expr;)*) => {
+    impl Q {
+      $(
+        $(#[doc = $doc])+
+        pub fn $name(self) -> io::Result<Vec<Option<$type>>> {
+          match self {
+            Q::$variant(list) => Ok(
+              list
+                .into_data()
+                .into_iter()
+                .map(|value| if $is_null(&value) { None } else { Some(value) })
+                .collect(),
+            ),
+            other => Err(mismatch(&other, $target)),
+          }
+        }
+      )*
+    }
+  };
+}
+
+impl_into_opt_vec! {
+  /// Convert a short list, mapping `0Nh` elements to `None`.
+  into_i16_opt_vec: ShortList => i16, |value: &i16| *value == i16::MIN, "Vec<Option<i16>>";
+  /// Convert an int list, mapping `0Ni` elements to `None`.
+  into_i32_opt_vec: IntList => i32, |value: &i32| *value == i32::MIN, "Vec<Option<i32>>";
+  /// Convert a long list, mapping `0N` elements to `None`.
+  into_i64_opt_vec: LongList => i64, |value: &i64| *value == i64::MIN, "Vec<Option<i64>>";
+  /// Convert a real list, mapping `0Ne` elements to `None`.
+  into_f32_opt_vec: RealList => f32, |value: &f32| value.is_nan(), "Vec<Option<f32>>";
+  /// Convert a float list, mapping `0n` elements to `None`.
+  into_f64_opt_vec: FloatList => f64, |value: &f64| value.is_nan(), "Vec<Option<f64>>";
+  /// Convert a symbol list, mapping `` ` `` elements to `None`.
+  into_string_opt_vec: SymbolList => String, |value: &String| value.is_empty(), "Vec<Option<String>>";
+  /// Convert a timestamp list to epoch offsets, mapping `0Np` elements to
+  ///  `None`.
+  into_timestamp_opt_vec: TimestampList => i64, |value: &i64| *value == i64::MIN, "Vec<Option<i64>>";
+  /// Convert a month list to epoch offsets, mapping `0Nm` elements to
+  ///  `None`.
+  into_month_opt_vec: MonthList => i32, |value: &i32| *value == i32::MIN, "Vec<Option<i32>>";
+  /// Convert a date list to epoch offsets, mapping `0Nd` elements to
+  ///  `None`.
+  into_date_opt_vec: DateList => i32, |value: &i32| *value == i32::MIN, "Vec<Option<i32>>";
+  /// Convert a datetime list to epoch offsets, mapping `0Nz` elements to
+  ///  `None`.
+  into_datetime_opt_vec: DatetimeList => f64, |value: &f64| value.is_nan(), "Vec<Option<f64>>";
+  /// Convert a timespan list to nanosecond counts, mapping `0Nn` elements
+  ///  to `None`.
+  into_timespan_opt_vec: TimespanList => i64, |value: &i64| *value == i64::MIN, "Vec<Option<i64>>";
+  /// Convert a minute list to minute counts, mapping `0Nu` elements to
+  ///  `None`.
+  into_minute_opt_vec: MinuteList => i32, |value: &i32| *value == i32::MIN, "Vec<Option<i32>>";
+  /// Convert a second list to second counts, mapping `0Nv` elements to
+  ///  `None`.
+  into_second_opt_vec: SecondList => i32, |value: &i32| *value == i32::MIN, "Vec<Option<i32>>";
+  /// Convert a time list to millisecond counts, mapping `0Nt` elements to
+  ///  `None`.
+  into_time_opt_vec: TimeList => i32, |value: &i32| *value == i32::MIN, "Vec<Option<i32>>";
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(feature = "chrono")]
+/// Nanoseconds between a datetime and the q epoch 2000.01.01D00:00:00,
+///  saturating outside the representable range.
+fn nanos_since_q_epoch(datetime: chrono::NaiveDateTime) -> i64 {
+  let epoch = chrono::NaiveDate::from_ymd_opt(2000, 1, 1)
+    .expect("a valid date")
+    .and_hms_opt(0, 0, 0)
+    .expect("a valid time");
+  saturating_nanos(datetime - epoch)
+}
+
+#[cfg(feature = "chrono")]
+/// Days between a date and the q epoch 2000.01.01.
+fn days_since_q_epoch(date: chrono::NaiveDate) -> i32 {
+  let epoch = chrono::NaiveDate::from_ymd_opt(2000, 1, 1).expect("a valid date");
+  (date - epoch).num_days() as i32
+}
+
+#[cfg(feature = "chrono")]
+/// Milliseconds since midnight, dropping sub-millisecond precision as the
+///  q time type does.
+fn millis_since_midnight(time: chrono::NaiveTime) -> i32 {
+  let midnight = chrono::NaiveTime::from_hms_opt(0, 0, 0).expect("a valid time");
+  (time - midnight).num_milliseconds() as i32
+}
+
+#[cfg(feature = "chrono")]
+/// Whole nanoseconds of a duration, saturating outside the `i64` range.
+fn saturating_nanos(duration: chrono::Duration) -> i64 {
+  duration.num_nanoseconds().unwrap_or(if duration < chrono::Duration::zero() {
+    i64::MIN
+  } else {
+    i64::MAX
+  })
+}
+
+/// Break a list of any kind into its items as q objects, promoting the
+///  elements of simple lists into the matching atoms.
+fn list_items(object: Q) -> io::Result<Vec<Q>> {
+  match object {
+    Q::MixedList(items) => Ok(items),
+    Q::BoolList(list) => Ok(list.into_data().into_iter().map(Q::Bool).collect()),
+    Q::GuidList(list) => Ok(list.into_data().into_iter().map(Q::Guid).collect()),
+    Q::ByteList(list) => Ok(list.into_data().into_iter().map(Q::Byte).collect()),
+    Q::ShortList(list) => Ok(list.into_data().into_iter().map(Q::Short).collect()),
+    Q::IntList(list) => Ok(list.into_data().into_iter().map(Q::Int).collect()),
+    Q::LongList(list) => Ok(list.into_data().into_iter().map(Q::Long).collect()),
+    Q::RealList(list) => Ok(list.into_data().into_iter().map(Q::Real).collect()),
+    Q::FloatList(list) => Ok(list.into_data().into_iter().map(Q::Float).collect()),
+    Q::String(text) => Ok(text.chars().map(Q::Char).collect()),
+    Q::SymbolList(list) => Ok(list.into_data().into_iter().map(Q::Symbol).collect()),
+    Q::TimestampList(list) => Ok(list.into_data().into_iter().map(Q::Timestamp).collect()),
+    Q::MonthList(list) => Ok(list.into_data().into_iter().map(Q::Month).collect()),
+    Q::DateList(list) => Ok(list.into_data().into_iter().map(Q::Date).collect()),
+    Q::DatetimeList(list) => Ok(list.into_data().into_iter().map(Q::Datetime).collect()),
+    Q::TimespanList(list) => Ok(list.into_data().into_iter().map(Q::Timespan).collect()),
+    Q::MinuteList(list) => Ok(list.into_data().into_iter().map(Q::Minute).collect()),
+    Q::SecondList(list) => Ok(list.into_data().into_iter().map(Q::Second).collect()),
+    Q::TimeList(list) => Ok(list.into_data().into_iter().map(Q::Time).collect()),
+    other => Err(mismatch(&other, "a list")),
+  }
+}
+
+/// Build the error returned when an object does not represent the
+///  requested Rust type.
+fn mismatch(object: &Q, target: &str) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::InvalidData,
+    format!("cannot convert q {} into {}", q_type_name(object), target),
+  )
+}
+
+/// q name of the type of an object, for error messages.
+pub(crate) fn q_type_name(object: &Q) -> &'static str {
+  match object {
+    Q::Bool(_) => "bool",
+    Q::Guid(_) => "guid",
+    Q::Byte(_) => "byte",
+    Q::Short(_) => "short",
+    Q::Int(_) => "int",
+    Q::Long(_) => "long",
+    Q::Real(_) => "real",
+    Q::Float(_) => "float",
+    Q::Char(_) => "char",
+    Q::Symbol(_) => "symbol",
+    Q::Timestamp(_) => "timestamp",
+    Q::Month(_) => "month",
+    Q::Date(_) => "date",
+    Q::Datetime(_) => "datetime",
+    Q::Timespan(_) => "timespan",
+    Q::Minute(_) => "minute",
+    Q::Second(_) => "second",
+    Q::Time(_) => "time",
+    Q::BoolList(_) => "bool list",
+    Q::GuidList(_) => "guid list",
+    Q::ByteList(_) => "byte list",
+    Q::ShortList(_) => "short list",
+    Q::IntList(_) => "int list",
+    Q::LongList(_) => "long list",
+    Q::RealList(_) => "real list",
+    Q::FloatList(_) => "float list",
+    Q::String(_) => "char list",
+    Q::SymbolList(_) => "symbol list",
+    Q::TimestampList(_) => "timestamp list",
+    Q::MonthList(_) => "month list",
+    Q::DateList(_) => "date list",
+    Q::DatetimeList(_) => "datetime list",
+    Q::TimespanList(_) => "timespan list",
+    Q::MinuteList(_) => "minute list",
+    Q::SecondList(_) => "second list",
+    Q::TimeList(_) => "time list",
+    Q::Enum(_) => "enum",
+    Q::MixedList(_) => "mixed list",
+    Q::Table(_) => "table",
+    Q::Dictionary(_) => "dictionary",
+    Q::Function(function) => match function {
+      crate::qtype::QFunction::Lambda { .. } => "lambda",
+      crate::qtype::QFunction::UnaryPrimitive(_) => "unary primitive",
+      crate::qtype::QFunction::Operator(_) => "operator",
+      crate::qtype::QFunction::Iterator(_) => "iterator",
+      crate::qtype::QFunction::Projection(_) => "projection",
+      crate::qtype::QFunction::Composition(_) => "composition",
+      crate::qtype::QFunction::Derived(..) => "derived function",
+    },
+    Q::Raw { .. } => "raw object",
+    Q::Error(_) => "error",
+    Q::Null => "general null",
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn primitive_and_container_conversions() {
+    assert_eq!(i64::from_q(Q::Long(42)).unwrap(), 42);
+    assert_eq!(
+      String::from_q(Q::Symbol("abc".to_string())).unwrap(),
+      "abc".to_string()
+    );
+    assert_eq!(
+      Vec::<i64>::from_q(Q::LongList(QList::new(vec![1, 2, 3]))).unwrap(),
+      vec![1, 2, 3]
+    );
+    assert_eq!(Option::<i64>::from_q(Q::Null).unwrap(), None);
+    assert_eq!(Option::<i64>::from_q(Q::Long(7)).unwrap(), Some(7));
+    let pair = <(String, f64)>::from_q(Q::MixedList(vec![
+      Q::Symbol("vwap".to_string()),
+      Q::Float(102.5),
+    ]))
+    .unwrap();
+    assert_eq!(pair, ("vwap".to_string(), 102.5));
+  }
+
+  #[test]
+  fn into_q_builds_typed_arguments() {
+    assert_eq!("upd".into_q(), Q::Symbol("upd".to_string()));
+    assert_eq!("abc".to_string().into_q(), Q::String("abc".to_string()));
+    assert_eq!(
+      vec![1i64, 2, 3].into_q(),
+      Q::LongList(QList::new(vec![1, 2, 3]))
+    );
+    assert_eq!(
+      vec![Q::Long(1), Q::Symbol("a".to_string())].into_q(),
+      Q::MixedList(vec![Q::Long(1), Q::Symbol("a".to_string())])
+    );
+    assert_eq!(
+      ("px", 102.5).into_q(),
+      Q::MixedList(vec![Q::Symbol("px".to_string()), Q::Float(102.5)])
+    );
+    assert_eq!(
+      crate::query!("insert", "trade", vec![1i64, 2]),
+      Q::MixedList(vec![
+        Q::Symbol("insert".to_string()),
+        Q::Symbol("trade".to_string()),
+        Q::LongList(QList::new(vec![1, 2])),
+      ])
+    );
+  }
+
+  #[cfg(feature = "chrono")]
+  #[test]
+  fn chrono_types_convert_to_temporal_atoms() {
+    let date = chrono::NaiveDate::from_ymd_opt(2000, 1, 2).unwrap();
+    assert_eq!(date.into_q(), Q::Date(1));
+    let time = chrono::NaiveTime::from_hms_milli_opt(0, 0, 1, 500).unwrap();
+    assert_eq!(time.into_q(), Q::Time(1500));
+    let datetime = date.and_hms_opt(0, 0, 0).unwrap();
+    assert_eq!(datetime.into_q(), Q::Timestamp(86_400_000_000_000));
+    assert_eq!(
+      chrono::Duration::seconds(1).into_q(),
+      Q::Timespan(1_000_000_000)
+    );
+  }
+
+  #[cfg(feature = "derive")]
+  #[test]
+  fn derived_records_round_trip_through_tables() {
+    use crate::qtype::QTable;
+
+    #[derive(crate::QRecord, Clone, Debug, PartialEq)]
+    struct Trade {
+      #[q(rename = "sym", qtype = "symbol")]
+      symbol: String,
+      #[q(qtype = "timestamp")]
+      time: i64,
+      price: f64,
+    }
+
+    let trade = Trade {
+      symbol: "abc".to_string(),
+      time: 1,
+      price: 102.5,
+    };
+    let dictionary = trade.clone().into_q();
+    assert_eq!(
+      dictionary,
+      Q::Dictionary(crate::qtype::QDictionary::new(
+        Q::SymbolList(QList::new(vec![
+          "sym".to_string(),
+          "time".to_string(),
+          "price".to_string(),
+        ])),
+        Q::MixedList(vec![
+          Q::Symbol("abc".to_string()),
+          Q::Timestamp(1),
+          Q::Float(102.5),
+        ]),
+      ))
+    );
+    assert_eq!(Trade::from_q(dictionary).unwrap(), trade);
+
+    let trades = vec![
+      trade.clone(),
+      Trade {
+        symbol: "def".to_string(),
+        time: 2,
+        price: 103.0,
+      },
+    ];
+    let table = trades.clone().into_q();
+    assert_eq!(
+      table,
+      Q::Table(
+        QTable::new(
+          vec!["sym".to_string(), "time".to_string(), "price".to_string()],
+          vec![
+            Q::SymbolList(QList::new(vec!["abc".to_string(), "def".to_string()])),
+            Q::TimestampList(QList::new(vec![1, 2])),
+            Q::FloatList(QList::new(vec![102.5, 103.0])),
+          ],
+        )
+        .unwrap()
+      )
+    );
+    assert_eq!(Vec::<Trade>::from_q(table.clone()).unwrap(), trades);
+    let table = match table {
+      Q::Table(table) => table,
+      _ => unreachable!("built as a table"),
+    };
+    assert_eq!(table.rows_as::<Trade>().unwrap(), trades);
+  }
+
+  #[test]
+  fn mismatches_name_both_sides() {
+    let error = i64::from_q(Q::Int(42)).expect_err("an int is not a long");
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "cannot convert q int into i64");
+    let error = <(i64, i64)>::from_q(Q::LongList(QList::new(vec![1, 2, 3])))
+      .expect_err("the arity must match");
+    assert!(error.to_string().contains("tuple"));
+  }
+
+  #[test]
+  fn null_atoms_convert_to_none() {
+    assert_eq!(Q::Long(42).into_i64_opt().unwrap(), Some(42));
+    assert_eq!(Q::Long(i64::MIN).into_i64_opt().unwrap(), None);
+    assert_eq!(Q::Float(f64::NAN).into_f64_opt().unwrap(), None);
+    assert_eq!(Q::Symbol(String::new()).into_string_opt().unwrap(), None);
+    assert_eq!(
+      Q::String(String::new()).into_string_opt().unwrap(),
+      Some(String::new())
+    );
+    assert!(Q::Int(42).into_i64_opt().is_err());
+  }
+
+  #[test]
+  fn null_elements_convert_to_none() {
+    assert_eq!(
+      Q::LongList(QList::new(vec![1, i64::MIN, 3]))
+        .into_i64_opt_vec()
+        .unwrap(),
+      vec![Some(1), None, Some(3)]
+    );
+    assert_eq!(
+      Q::DateList(QList::new(vec![7000, i32::MIN]))
+        .into_date_opt_vec()
+        .unwrap(),
+      vec![Some(7000), None]
+    );
+    assert_eq!(
+      Q::SymbolList(QList::new(vec!["abc".to_string(), String::new()]))
+        .into_string_opt_vec()
+        .unwrap(),
+      vec![Some("abc".to_string()), None]
+    );
+    assert!(Q::Long(42).into_i64_opt_vec().is_err());
+  }
+}
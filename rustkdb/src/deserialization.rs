@@ -0,0 +1,729 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Deserialization of kdb+ IPC message bodies into [`Q`] objects.
+//!
+//! Both little and big endian messages are understood; the endianness is
+//! taken from the first byte of the message header by the caller.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::io;
+
+use crate::qtype::*;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Cursor over a message body keeping track of the encoding of the message.
+struct Reader<'a> {
+  /// Message body excluding the 8 byte header.
+  bytes: &'a [u8],
+  /// Current read position.
+  position: usize,
+  /// `true` if the message was encoded little endian.
+  little_endian: bool,
+}
+
+/// Layout of a serialized table body prepared by [`begin_table`]: the
+///  column names together with the type and the starting byte position of
+///  every column, so the columns can be decoded selectively or in row
+///  batches by [`read_column_batch`].
+pub(crate) struct TableLayout {
+  /// Column names in order.
+  pub(crate) columns: Vec<String>,
+  /// Type code of each column list.
+  pub(crate) column_types: Vec<i8>,
+  /// Byte position of the first element of each column.
+  pub(crate) positions: Vec<usize>,
+  /// Number of rows in the table.
+  pub(crate) row_count: usize,
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Fail a message that consists of nothing but an error atom — the wire
+///  form of `'message` signalled by the remote process — while embedded
+///  [`Q::Error`] values pass through untouched.
+fn surface_error(object: Q) -> io::Result<Q> {
+  match object {
+    Q::Error(message) => Err(io::Error::other(format!("query error: {}", message))),
+    other => Ok(other),
+  }
+}
+
+/// Build an error denoting a malformed message.
+fn broken_message(reason: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, format!("broken message: {}", reason))
+}
+
+impl<'a> Reader<'a> {
+  /// Construct a reader over a message body.
+  fn new(bytes: &'a [u8], little_endian: bool) -> Self {
+    Reader {
+      bytes,
+      position: 0,
+      little_endian,
+    }
+  }
+
+  /// Take the next `n` bytes from the message.
+  fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+    if self.position + n > self.bytes.len() {
+      return Err(broken_message("unexpected end of message"));
+    }
+    let slice = &self.bytes[self.position..self.position + n];
+    self.position += n;
+    Ok(slice)
+  }
+
+  fn read_u8(&mut self) -> io::Result<u8> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn read_i8(&mut self) -> io::Result<i8> {
+    Ok(self.take(1)?[0] as i8)
+  }
+
+  fn read_i16(&mut self) -> io::Result<i16> {
+    let bytes: [u8; 2] = self.take(2)?.try_into().unwrap();
+    Ok(if self.little_endian {
+      i16::from_le_bytes(bytes)
+    } else {
+      i16::from_be_bytes(bytes)
+    })
+  }
+
+  fn read_i32(&mut self) -> io::Result<i32> {
+    let bytes: [u8; 4] = self.take(4)?.try_into().unwrap();
+    Ok(if self.little_endian {
+      i32::from_le_bytes(bytes)
+    } else {
+      i32::from_be_bytes(bytes)
+    })
+  }
+
+  fn read_i64(&mut self) -> io::Result<i64> {
+    let bytes: [u8; 8] = self.take(8)?.try_into().unwrap();
+    Ok(if self.little_endian {
+      i64::from_le_bytes(bytes)
+    } else {
+      i64::from_be_bytes(bytes)
+    })
+  }
+
+  fn read_f32(&mut self) -> io::Result<f32> {
+    Ok(f32::from_bits(self.read_i32()? as u32))
+  }
+
+  fn read_f64(&mut self) -> io::Result<f64> {
+    Ok(f64::from_bits(self.read_i64()? as u64))
+  }
+
+  /// Read a list length, i.e. attribute byte followed by a 4 byte length.
+  fn read_list_header(&mut self) -> io::Result<(Attribute, usize)> {
+    let attribute = Attribute::from_byte(self.read_u8()?);
+    let length = self.read_i32()? as usize;
+    Ok((attribute, length))
+  }
+
+  /// Read a null terminated symbol.
+  fn read_symbol(&mut self) -> io::Result<String> {
+    let start = self.position;
+    while self.position < self.bytes.len() && self.bytes[self.position] != 0 {
+      self.position += 1;
+    }
+    if self.position == self.bytes.len() {
+      return Err(broken_message("unterminated symbol"));
+    }
+    let symbol = String::from_utf8_lossy(&self.bytes[start..self.position]).into_owned();
+    // Skip the null terminator
+    self.position += 1;
+    Ok(symbol)
+  }
+
+  /// Read a simple list of fixed width items.
+  fn read_list<T, F>(&mut self, read_item: F) -> io::Result<QList<T>>
+  where
+    F: Fn(&mut Self) -> io::Result<T>,
+  {
+    let (attribute, length) = self.read_list_header()?;
+    let mut data = Vec::with_capacity(length);
+    for _ in 0..length {
+      data.push(read_item(self)?);
+    }
+    Ok(QList::with_attribute(data, attribute))
+  }
+
+  /// Read one q object starting at the current position.
+  fn read_q(&mut self) -> io::Result<Q> {
+    let type_code = self.read_i8()?;
+    match type_code {
+      Q_ERROR => {
+        // Error atom, e.g. 'type. A gateway may embed one inside a compound
+        //  response; only a whole-message error fails the call, in
+        //  [`surface_error`].
+        Ok(Q::Error(self.read_symbol()?))
+      }
+      -1 => Ok(Q::Bool(self.read_u8()? != 0)),
+      -2 => Ok(Q::Guid(self.take(16)?.try_into().unwrap())),
+      -4 => Ok(Q::Byte(self.read_u8()?)),
+      -5 => Ok(Q::Short(self.read_i16()?)),
+      -6 => Ok(Q::Int(self.read_i32()?)),
+      -7 => Ok(Q::Long(self.read_i64()?)),
+      -8 => Ok(Q::Real(self.read_f32()?)),
+      -9 => Ok(Q::Float(self.read_f64()?)),
+      -10 => Ok(Q::Char(self.read_u8()? as char)),
+      -11 => Ok(Q::Symbol(self.read_symbol()?)),
+      -12 => Ok(Q::Timestamp(self.read_i64()?)),
+      -13 => Ok(Q::Month(self.read_i32()?)),
+      -14 => Ok(Q::Date(self.read_i32()?)),
+      -15 => Ok(Q::Datetime(self.read_f64()?)),
+      -16 => Ok(Q::Timespan(self.read_i64()?)),
+      -17 => Ok(Q::Minute(self.read_i32()?)),
+      -18 => Ok(Q::Second(self.read_i32()?)),
+      -19 => Ok(Q::Time(self.read_i32()?)),
+      1 => Ok(Q::BoolList(self.read_list(|reader| Ok(reader.read_u8()? != 0))?)),
+      2 => Ok(Q::GuidList(
+        self.read_list(|reader| Ok(reader.take(16)?.try_into().unwrap()))?,
+      )),
+      4 => Ok(Q::ByteList(self.read_list(|reader| reader.read_u8())?)),
+      5 => Ok(Q::ShortList(self.read_list(|reader| reader.read_i16())?)),
+      6 => Ok(Q::IntList(self.read_list(|reader| reader.read_i32())?)),
+      7 => Ok(Q::LongList(self.read_list(|reader| reader.read_i64())?)),
+      8 => Ok(Q::RealList(self.read_list(|reader| reader.read_f32())?)),
+      9 => Ok(Q::FloatList(self.read_list(|reader| reader.read_f64())?)),
+      10 => {
+        let (_, length) = self.read_list_header()?;
+        let bytes = self.take(length)?;
+        Ok(Q::String(String::from_utf8_lossy(bytes).into_owned()))
+      }
+      11 => Ok(Q::SymbolList(self.read_list(|reader| reader.read_symbol())?)),
+      12 => Ok(Q::TimestampList(self.read_list(|reader| reader.read_i64())?)),
+      13 => Ok(Q::MonthList(self.read_list(|reader| reader.read_i32())?)),
+      14 => Ok(Q::DateList(self.read_list(|reader| reader.read_i32())?)),
+      15 => Ok(Q::DatetimeList(self.read_list(|reader| reader.read_f64())?)),
+      16 => Ok(Q::TimespanList(self.read_list(|reader| reader.read_i64())?)),
+      17 => Ok(Q::MinuteList(self.read_list(|reader| reader.read_i32())?)),
+      18 => Ok(Q::SecondList(self.read_list(|reader| reader.read_i32())?)),
+      19 => Ok(Q::TimeList(self.read_list(|reader| reader.read_i32())?)),
+      Q_ENUM_MIN..=Q_ENUM_MAX => Ok(Q::Enum(QEnum::new(
+        type_code,
+        self.read_list(|reader| reader.read_i64())?,
+      )?)),
+      Q_MIXED_LIST => {
+        let (_, length) = self.read_list_header()?;
+        let mut items = Vec::with_capacity(length);
+        for _ in 0..length {
+          items.push(self.read_q()?);
+        }
+        Ok(Q::MixedList(items))
+      }
+      Q_TABLE => {
+        // Attribute byte of the table followed by the underlying dictionary.
+        let _ = self.read_u8()?;
+        if self.read_i8()? != Q_DICTIONARY {
+          return Err(broken_message("table does not contain a dictionary"));
+        }
+        let columns = match self.read_q()? {
+          Q::SymbolList(columns) => columns.into_data(),
+          _ => return Err(broken_message("table columns are not a symbol list")),
+        };
+        let values = match self.read_q()? {
+          Q::MixedList(values) => values,
+          _ => return Err(broken_message("table values are not a mixed list")),
+        };
+        QTable::new(columns, values).map(Q::Table)
+      }
+      Q_DICTIONARY | Q_SORTED_DICTIONARY => {
+        let keys = self.read_q()?;
+        let values = self.read_q()?;
+        Ok(Q::Dictionary(if type_code == Q_SORTED_DICTIONARY {
+          QDictionary::sorted(keys, values)
+        } else {
+          QDictionary::new(keys, values)
+        }))
+      }
+      Q_LAMBDA => {
+        let context = self.read_symbol()?;
+        let body = match self.read_q()? {
+          Q::String(body) => body,
+          _ => return Err(broken_message("lambda body is not a char list")),
+        };
+        Ok(Q::Function(QFunction::Lambda { context, body }))
+      }
+      // 101h carries both the generic null `(::)` and the unary primitives.
+      Q_GENERAL_NULL => match self.read_u8()? {
+        0 => Ok(Q::Null),
+        code => Ok(Q::Function(QFunction::UnaryPrimitive(code))),
+      },
+      Q_OPERATOR => Ok(Q::Function(QFunction::Operator(self.read_u8()?))),
+      Q_ITERATOR => Ok(Q::Function(QFunction::Iterator(self.read_u8()?))),
+      Q_PROJECTION | Q_COMPOSITION => {
+        let length = self.read_i32()? as usize;
+        let mut items = Vec::with_capacity(length);
+        for _ in 0..length {
+          items.push(self.read_q()?);
+        }
+        Ok(Q::Function(if type_code == Q_PROJECTION {
+          QFunction::Projection(items)
+        } else {
+          QFunction::Composition(items)
+        }))
+      }
+      // Functions derived by each-, over-, scan- and the prior/right/left
+      //  iterator forms.
+      106..=111 => Ok(Q::Function(QFunction::Derived(
+        type_code,
+        Box::new(self.read_q()?),
+      ))),
+      _ => {
+        // Unknown type, e.g. a foreign object. The length cannot be
+        //  determined, so keep the rest of the message as raw bytes.
+        let bytes = self.take(self.bytes.len() - self.position)?.to_vec();
+        Ok(Q::Raw { type_code, bytes })
+      }
+    }
+  }
+
+  /// Read `count` elements of a list of the given type, without a list
+  ///  header, as a list object of that type.
+  fn read_elements(&mut self, type_code: i8, count: usize) -> io::Result<Q> {
+    fn collect<T, F>(reader: &mut Reader, count: usize, read_item: F) -> io::Result<QList<T>>
+    where
+      F: Fn(&mut Reader) -> io::Result<T>,
+    {
+      let mut data = Vec::with_capacity(count);
+      for _ in 0..count {
+        data.push(read_item(reader)?);
+      }
+      Ok(QList::new(data))
+    }
+    match type_code {
+      1 => Ok(Q::BoolList(collect(self, count, |reader| {
+        Ok(reader.read_u8()? != 0)
+      })?)),
+      2 => Ok(Q::GuidList(collect(self, count, |reader| {
+        Ok(reader.take(16)?.try_into().unwrap())
+      })?)),
+      4 => Ok(Q::ByteList(collect(self, count, |reader| reader.read_u8())?)),
+      5 => Ok(Q::ShortList(collect(self, count, |reader| reader.read_i16())?)),
+      6 => Ok(Q::IntList(collect(self, count, |reader| reader.read_i32())?)),
+      7 => Ok(Q::LongList(collect(self, count, |reader| reader.read_i64())?)),
+      8 => Ok(Q::RealList(collect(self, count, |reader| reader.read_f32())?)),
+      9 => Ok(Q::FloatList(collect(self, count, |reader| reader.read_f64())?)),
+      10 => {
+        let bytes = self.take(count)?;
+        Ok(Q::String(String::from_utf8_lossy(bytes).into_owned()))
+      }
+      11 => Ok(Q::SymbolList(collect(self, count, |reader| {
+        reader.read_symbol()
+      })?)),
+      12 => Ok(Q::TimestampList(collect(self, count, |reader| {
+        reader.read_i64()
+      })?)),
+      13 => Ok(Q::MonthList(collect(self, count, |reader| reader.read_i32())?)),
+      14 => Ok(Q::DateList(collect(self, count, |reader| reader.read_i32())?)),
+      15 => Ok(Q::DatetimeList(collect(self, count, |reader| {
+        reader.read_f64()
+      })?)),
+      16 => Ok(Q::TimespanList(collect(self, count, |reader| {
+        reader.read_i64()
+      })?)),
+      17 => Ok(Q::MinuteList(collect(self, count, |reader| reader.read_i32())?)),
+      18 => Ok(Q::SecondList(collect(self, count, |reader| reader.read_i32())?)),
+      19 => Ok(Q::TimeList(collect(self, count, |reader| reader.read_i32())?)),
+      Q_ENUM_MIN..=Q_ENUM_MAX => Ok(Q::Enum(QEnum::new(
+        type_code,
+        collect(self, count, |reader| reader.read_i64())?,
+      )?)),
+      Q_MIXED_LIST => {
+        let mut items = Vec::with_capacity(count);
+        for _ in 0..count {
+          items.push(self.read_q()?);
+        }
+        Ok(Q::MixedList(items))
+      }
+      _ => Err(broken_message(&format!(
+        "unsupported column type: {}",
+        type_code
+      ))),
+    }
+  }
+
+  /// Skip `count` elements of a list of the given type.
+  fn skip_elements(&mut self, type_code: i8, count: usize) -> io::Result<()> {
+    match type_code {
+      1 | 4 | 10 => self.take(count).map(|_| ()),
+      2 => self.take(16 * count).map(|_| ()),
+      5 => self.take(2 * count).map(|_| ()),
+      6 | 8 | 13 | 14 | 17 | 18 | 19 => self.take(4 * count).map(|_| ()),
+      7 | 9 | 12 | 15 | 16 | Q_ENUM_MIN..=Q_ENUM_MAX => self.take(8 * count).map(|_| ()),
+      11 => {
+        for _ in 0..count {
+          self.read_symbol()?;
+        }
+        Ok(())
+      }
+      Q_MIXED_LIST => {
+        for _ in 0..count {
+          self.read_q()?;
+        }
+        Ok(())
+      }
+      _ => Err(broken_message(&format!(
+        "unsupported column type: {}",
+        type_code
+      ))),
+    }
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Deserialize a message body into a q object.
+/// # Parameters
+/// - `bytes`: Message body excluding the 8 byte header.
+/// - `little_endian`: `true` if the endianness flag of the header was 1.
+pub(crate) fn deserialize_q(bytes: &[u8], little_endian: bool) -> io::Result<Q> {
+  Reader::new(bytes, little_endian).read_q().and_then(surface_error)
+}
+
+/// Deserialize one q object from the front of `bytes`, returning the object
+///  together with the number of bytes consumed. Used where objects are
+///  stored back to back, e.g. in tickerplant log files.
+pub(crate) fn deserialize_q_prefix(bytes: &[u8], little_endian: bool) -> io::Result<(Q, usize)> {
+  let mut reader = Reader::new(bytes, little_endian);
+  let q = surface_error(reader.read_q()?)?;
+  Ok((q, reader.position))
+}
+
+/// Decompress a compressed message body.
+/// # Parameters
+/// - `bytes`: Compressed message body excluding the 8 byte header, i.e.
+///   starting with the 4 byte uncompressed total message size.
+/// - `little_endian`: `true` if the endianness flag of the header was 1.
+pub(crate) fn decompress(bytes: &[u8], little_endian: bool) -> io::Result<Vec<u8>> {
+  if bytes.len() < 4 {
+    return Err(broken_message("compressed message too short"));
+  }
+  let size_bytes: [u8; 4] = bytes[0..4].try_into().unwrap();
+  let total_size = if little_endian {
+    u32::from_le_bytes(size_bytes)
+  } else {
+    u32::from_be_bytes(size_bytes)
+  } as usize;
+  if total_size < 8 {
+    return Err(broken_message("invalid uncompressed size"));
+  }
+  // The uncompressed size includes the 8 byte message header.
+  let mut dst = vec![0u8; total_size - 8];
+  let src = &bytes[4..];
+  let mut index_map = [0usize; 256];
+  let mut d = 0;
+  let mut s = 0;
+  let mut p = 0;
+  let mut i: u32 = 0;
+  let mut f = 0u8;
+  let mut n = 0usize;
+  while s < dst.len() {
+    if i == 0 {
+      f = *src.get(d).ok_or_else(|| broken_message("truncated compressed data"))?;
+      d += 1;
+      i = 1;
+    }
+    if f & (i as u8) != 0 {
+      let mut r = index_map[*src.get(d).ok_or_else(|| broken_message("truncated compressed data"))? as usize];
+      d += 1;
+      n = *src.get(d).ok_or_else(|| broken_message("truncated compressed data"))? as usize;
+      d += 1;
+      // A copy run writes 2 + n bytes; a header that under-declares the
+      //  uncompressed size must not drive the writes out of bounds. The
+      //  reads stay behind the writes because `r` always points before `s`.
+      if s + 2 + n > dst.len() {
+        return Err(broken_message("compressed data overruns declared size"));
+      }
+      dst[s] = dst[r];
+      s += 1;
+      r += 1;
+      dst[s] = dst[r];
+      s += 1;
+      r += 1;
+      for m in 0..n {
+        dst[s + m] = dst[r + m];
+      }
+    } else {
+      dst[s] = *src.get(d).ok_or_else(|| broken_message("truncated compressed data"))?;
+      d += 1;
+      s += 1;
+    }
+    while p < s.saturating_sub(1) {
+      index_map[(dst[p] ^ dst[p + 1]) as usize] = p;
+      p += 1;
+    }
+    if f & (i as u8) != 0 {
+      s += n;
+      p = s;
+    }
+    i *= 2;
+    if i == 256 {
+      i = 0;
+    }
+  }
+  Ok(dst)
+}
+
+/// Parse the envelope of a serialized table body without decoding the column
+///  data, recording the type and the starting byte position of every column.
+/// # Parameters
+/// - `bytes`: Message body excluding the 8 byte header; must hold a table.
+/// - `little_endian`: `true` if the endianness flag of the header was 1.
+pub(crate) fn begin_table(bytes: &[u8], little_endian: bool) -> io::Result<TableLayout> {
+  let mut reader = Reader::new(bytes, little_endian);
+  match reader.read_i8()? {
+    Q_TABLE => (),
+    Q_ERROR => {
+      let message = reader.read_symbol()?;
+      return Err(io::Error::other(format!("query error: {}", message)));
+    }
+    type_code => {
+      return Err(broken_message(&format!(
+        "expected a table, got type {}",
+        type_code
+      )))
+    }
+  }
+  // Attribute byte of the table followed by the underlying dictionary.
+  let _ = reader.read_u8()?;
+  if reader.read_i8()? != Q_DICTIONARY {
+    return Err(broken_message("table does not contain a dictionary"));
+  }
+  let columns = match reader.read_q()? {
+    Q::SymbolList(columns) => columns.into_data(),
+    _ => return Err(broken_message("table columns are not a symbol list")),
+  };
+  if reader.read_i8()? != Q_MIXED_LIST {
+    return Err(broken_message("table values are not a mixed list"));
+  }
+  let (_, value_count) = reader.read_list_header()?;
+  if value_count != columns.len() {
+    return Err(broken_message("table values do not match the columns"));
+  }
+  let mut column_types = Vec::with_capacity(columns.len());
+  let mut positions = Vec::with_capacity(columns.len());
+  let mut row_count = 0;
+  for (index, _) in columns.iter().enumerate() {
+    let type_code = reader.read_i8()?;
+    if type_code < 0 {
+      return Err(broken_message("table column is not a list"));
+    }
+    let (_, length) = reader.read_list_header()?;
+    if index == 0 {
+      row_count = length;
+    } else if length != row_count {
+      return Err(broken_message("table columns have different lengths"));
+    }
+    column_types.push(type_code);
+    positions.push(reader.position);
+    reader.skip_elements(type_code, length)?;
+  }
+  Ok(TableLayout {
+    columns,
+    column_types,
+    positions,
+    row_count,
+  })
+}
+
+/// Decode the next `count` elements of one column of a table body prepared by
+///  [`begin_table`], advancing `position` past them.
+/// # Parameters
+/// - `bytes`: Message body the layout was prepared from.
+/// - `little_endian`: `true` if the endianness flag of the header was 1.
+/// - `type_code`: Type code of the column list.
+/// - `position`: Byte position of the next unread element of the column.
+/// - `count`: Number of elements to decode.
+pub(crate) fn read_column_batch(
+  bytes: &[u8],
+  little_endian: bool,
+  type_code: i8,
+  position: &mut usize,
+  count: usize,
+) -> io::Result<Q> {
+  let mut reader = Reader::new(bytes, little_endian);
+  reader.position = *position;
+  let chunk = reader.read_elements(type_code, count)?;
+  *position = reader.position;
+  Ok(chunk)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::serialization::serialize_q;
+
+  /// Serialize and deserialize again, expecting the original object back.
+  fn roundtrip(q: Q) {
+    let mut bytes = Vec::new();
+    serialize_q(&q, &mut bytes);
+    let restored = deserialize_q(&bytes, true).expect("deserialize");
+    assert_eq!(q, restored);
+  }
+
+  #[test]
+  fn malformed_compressed_data_is_an_error() {
+    // Uncompressed size of 10 declares a 2 byte payload, but the first
+    //  copy run alone would write 2 + 255 bytes.
+    let bytes = [10u8, 0, 0, 0, 1, 0, 255];
+    let error = decompress(&bytes, true).expect_err("overrun");
+    assert!(error.to_string().contains("overruns declared size"));
+  }
+
+  #[test]
+  fn roundtrip_atoms() {
+    roundtrip(Q::Bool(true));
+    roundtrip(Q::Byte(0x2a));
+    roundtrip(Q::Short(42));
+    roundtrip(Q::Int(42));
+    roundtrip(Q::Long(42));
+    roundtrip(Q::Real(4.2));
+    roundtrip(Q::Float(4.2));
+    roundtrip(Q::Char('a'));
+    roundtrip(Q::Symbol("abc".to_string()));
+    roundtrip(Q::Timestamp(86_400_000_000_000));
+    roundtrip(Q::Date(7000));
+    roundtrip(Q::Null);
+  }
+
+  #[test]
+  fn roundtrip_lists() {
+    roundtrip(Q::BoolList(QList::new(vec![true, false, true])));
+    roundtrip(Q::LongList(QList::with_attribute(
+      vec![1, 2, 3],
+      Attribute::Sorted,
+    )));
+    roundtrip(Q::FloatList(QList::new(vec![1.0, 2.5])));
+    roundtrip(Q::String("select from trade".to_string()));
+    roundtrip(Q::SymbolList(QList::new(vec![
+      "abc".to_string(),
+      "def".to_string(),
+    ])));
+    roundtrip(Q::MixedList(vec![
+      Q::Long(1),
+      Q::Symbol("abc".to_string()),
+      Q::Float(4.2),
+    ]));
+  }
+
+  #[test]
+  fn roundtrip_functions() {
+    roundtrip(Q::Function(QFunction::Lambda {
+      context: "".to_string(),
+      body: "{x+y}".to_string(),
+    }));
+    roundtrip(Q::Function(QFunction::UnaryPrimitive(41)));
+    roundtrip(Q::Function(QFunction::Operator(1)));
+    roundtrip(Q::Function(QFunction::Iterator(0)));
+    // `+[1]`: the operator with its first argument fixed.
+    roundtrip(Q::Function(QFunction::Projection(vec![
+      Q::Function(QFunction::Operator(1)),
+      Q::Long(1),
+    ])));
+    roundtrip(Q::Function(QFunction::Composition(vec![
+      Q::Function(QFunction::UnaryPrimitive(41)),
+      Q::Function(QFunction::Operator(1)),
+    ])));
+    // `+/`: the operator modified by over.
+    roundtrip(Q::Function(QFunction::Derived(
+      107,
+      Box::new(Q::Function(QFunction::Operator(1))),
+    )));
+    // The zero byte of 101h stays the generic null, not a primitive.
+    roundtrip(Q::Null);
+  }
+
+  #[test]
+  fn roundtrip_compound() {
+    let table = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+        Q::FloatList(QList::new(vec![1.0, 2.0])),
+      ],
+    )
+    .expect("table");
+    roundtrip(Q::Table(table.clone()));
+    roundtrip(Q::Dictionary(QDictionary::new(
+      Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+      Q::LongList(QList::new(vec![1, 2])),
+    )));
+    // A sorted dictionary travels as type 127h and stays sorted.
+    roundtrip(Q::Dictionary(QDictionary::sorted(
+      Q::SymbolList(QList::with_attribute(
+        vec!["a".to_string(), "b".to_string()],
+        Attribute::Sorted,
+      )),
+      Q::LongList(QList::new(vec![1, 2])),
+    )));
+  }
+
+  #[test]
+  fn error_response_becomes_error() {
+    // -128 followed by a null terminated message
+    let bytes = [0x80, b't', b'y', b'p', b'e', 0x00];
+    let result = deserialize_q(&bytes, true);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("type"));
+  }
+
+  #[test]
+  fn enumerated_symbols_keep_their_indices() {
+    let enumeration = QEnum::new(
+      20,
+      QList::with_attribute(vec![1, 0, 1], Attribute::Grouped),
+    )
+    .expect("enum");
+    roundtrip(Q::Enum(enumeration.clone()));
+    let domain = vec!["abc".to_string(), "def".to_string()];
+    assert_eq!(
+      enumeration.resolve(&domain).expect("resolve"),
+      Q::SymbolList(QList::with_attribute(
+        vec!["def".to_string(), "abc".to_string(), "def".to_string()],
+        Attribute::Grouped,
+      ))
+    );
+    assert!(enumeration.resolve(&domain[..1]).is_err());
+  }
+
+  #[test]
+  fn unknown_types_survive_as_raw_bytes() {
+    // 112h is a foreign object; this crate cannot interpret it but must
+    //  not abort the message either.
+    roundtrip(Q::Raw {
+      type_code: 112,
+      bytes: vec![0x01, 0x02, 0x03],
+    });
+  }
+
+  #[test]
+  fn embedded_errors_become_values() {
+    // A gateway fanning out to two processes may return one result and
+    //  one error in the same compound list.
+    roundtrip(Q::MixedList(vec![
+      Q::Long(42),
+      Q::Error("noent".to_string()),
+    ]));
+  }
+}
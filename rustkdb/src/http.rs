@@ -0,0 +1,377 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! One-shot queries over the kdb+ HTTP interface (`.z.ph`).
+//!
+//! The query is issued against the `.json` endpoint served by kdb+ 3.6 and
+//! later, and the JSON response is parsed back into a [`Q`] object. As JSON
+//! has no q type information, numbers come back as floats, temporal values as
+//! strings and tables as mixed lists of dictionaries.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::io;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::qtype::{Q, QDictionary, QList};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Percent-encode a query for use in a URL.
+fn percent_encode(query: &str) -> String {
+  let mut encoded = String::with_capacity(query.len());
+  for byte in query.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+        encoded.push(byte as char);
+      }
+      _ => {
+        encoded.push('%');
+        encoded.push_str(&format!("{:02X}", byte));
+      }
+    }
+  }
+  encoded
+}
+
+/// Build an error denoting a malformed JSON response.
+fn broken_json(reason: &str) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::InvalidData,
+    format!("broken JSON response: {}", reason),
+  )
+}
+
+/// Cursor over a JSON document.
+struct JsonReader<'a> {
+  /// The whole document.
+  bytes: &'a [u8],
+  /// Current read position.
+  position: usize,
+}
+
+impl<'a> JsonReader<'a> {
+  fn new(bytes: &'a [u8]) -> Self {
+    JsonReader { bytes, position: 0 }
+  }
+
+  /// Current byte without consuming it.
+  fn peek(&self) -> Option<u8> {
+    self.bytes.get(self.position).copied()
+  }
+
+  /// Advance past whitespace.
+  fn skip_whitespace(&mut self) {
+    while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+      self.position += 1;
+    }
+  }
+
+  /// Consume the given literal or fail.
+  fn expect(&mut self, literal: &str) -> io::Result<()> {
+    if self.bytes[self.position..].starts_with(literal.as_bytes()) {
+      self.position += literal.len();
+      Ok(())
+    } else {
+      Err(broken_json(&format!("expected `{}`", literal)))
+    }
+  }
+
+  /// Read one JSON value.
+  fn read_value(&mut self) -> io::Result<Q> {
+    self.skip_whitespace();
+    match self.peek().ok_or_else(|| broken_json("unexpected end"))? {
+      b'n' => {
+        self.expect("null")?;
+        Ok(Q::Null)
+      }
+      b't' => {
+        self.expect("true")?;
+        Ok(Q::Bool(true))
+      }
+      b'f' => {
+        self.expect("false")?;
+        Ok(Q::Bool(false))
+      }
+      b'"' => Ok(Q::String(self.read_string()?)),
+      b'[' => self.read_array(),
+      b'{' => self.read_object(),
+      _ => self.read_number(),
+    }
+  }
+
+  /// Read a JSON string assuming the cursor is on the opening quote.
+  fn read_string(&mut self) -> io::Result<String> {
+    self.expect("\"")?;
+    let mut value = String::new();
+    loop {
+      match self.peek().ok_or_else(|| broken_json("unterminated string"))? {
+        b'"' => {
+          self.position += 1;
+          return Ok(value);
+        }
+        b'\\' => {
+          self.position += 1;
+          let escape = self
+            .peek()
+            .ok_or_else(|| broken_json("unterminated escape"))?;
+          self.position += 1;
+          match escape {
+            b'"' => value.push('"'),
+            b'\\' => value.push('\\'),
+            b'/' => value.push('/'),
+            b'b' => value.push('\u{8}'),
+            b'f' => value.push('\u{c}'),
+            b'n' => value.push('\n'),
+            b'r' => value.push('\r'),
+            b't' => value.push('\t'),
+            b'u' => {
+              if self.position + 4 > self.bytes.len() {
+                return Err(broken_json("truncated unicode escape"));
+              }
+              let hex = std::str::from_utf8(&self.bytes[self.position..self.position + 4])
+                .map_err(|_| broken_json("invalid unicode escape"))?;
+              let code =
+                u32::from_str_radix(hex, 16).map_err(|_| broken_json("invalid unicode escape"))?;
+              value.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+              self.position += 4;
+            }
+            _ => return Err(broken_json("unknown escape")),
+          }
+        }
+        _ => {
+          // Consume one UTF-8 encoded character.
+          let start = self.position;
+          self.position += 1;
+          while self.position < self.bytes.len() && (self.bytes[self.position] & 0xc0) == 0x80 {
+            self.position += 1;
+          }
+          value.push_str(&String::from_utf8_lossy(&self.bytes[start..self.position]));
+        }
+      }
+    }
+  }
+
+  /// Read a JSON number. q serializes all numbers as floats, so the value
+  ///  comes back as a float atom.
+  fn read_number(&mut self) -> io::Result<Q> {
+    let start = self.position;
+    while matches!(
+      self.peek(),
+      Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+    ) {
+      self.position += 1;
+    }
+    let text = std::str::from_utf8(&self.bytes[start..self.position])
+      .map_err(|_| broken_json("invalid number"))?;
+    text
+      .parse::<f64>()
+      .map(Q::Float)
+      .map_err(|_| broken_json("invalid number"))
+  }
+
+  /// Read a JSON array into a mixed list.
+  fn read_array(&mut self) -> io::Result<Q> {
+    self.expect("[")?;
+    let mut items = Vec::new();
+    self.skip_whitespace();
+    if self.peek() == Some(b']') {
+      self.position += 1;
+      return Ok(Q::MixedList(items));
+    }
+    loop {
+      items.push(self.read_value()?);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(b',') => self.position += 1,
+        Some(b']') => {
+          self.position += 1;
+          return Ok(Q::MixedList(items));
+        }
+        _ => return Err(broken_json("expected `,` or `]`")),
+      }
+    }
+  }
+
+  /// Read a JSON object into a dictionary with symbol keys.
+  fn read_object(&mut self) -> io::Result<Q> {
+    self.expect("{")?;
+    let mut keys = Vec::new();
+    let mut values = Vec::new();
+    self.skip_whitespace();
+    if self.peek() == Some(b'}') {
+      self.position += 1;
+      return Ok(Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(keys)),
+        Q::MixedList(values),
+      )));
+    }
+    loop {
+      self.skip_whitespace();
+      keys.push(self.read_string()?);
+      self.skip_whitespace();
+      self.expect(":")?;
+      values.push(self.read_value()?);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(b',') => self.position += 1,
+        Some(b'}') => {
+          self.position += 1;
+          return Ok(Q::Dictionary(QDictionary::new(
+            Q::SymbolList(QList::new(keys)),
+            Q::MixedList(values),
+          )));
+        }
+        _ => return Err(broken_json("expected `,` or `}`")),
+      }
+    }
+  }
+}
+
+/// Parse a JSON document into a q object.
+fn parse_json(bytes: &[u8]) -> io::Result<Q> {
+  let mut reader = JsonReader::new(bytes);
+  let value = reader.read_value()?;
+  reader.skip_whitespace();
+  if reader.position != reader.bytes.len() {
+    return Err(broken_json("trailing data"));
+  }
+  Ok(value)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Run a one-shot query over the kdb+ HTTP interface and parse the JSON
+///  response into a q object.
+/// # Parameters
+/// - `host`: Target hostname.
+/// - `port`: Target port serving HTTP, i.e. the normal listening port.
+/// - `query`: Query to execute remotely.
+/// # Example
+/// ```no_run
+/// # use rustkdb::http::http_query;
+/// # #[tokio::main] async fn main() -> std::io::Result<()> {
+/// let result = http_query("localhost", 5000, "count trade").await?;
+/// # Ok(())}
+/// ```
+pub async fn http_query(host: &str, port: u16, query: &str) -> io::Result<Q> {
+  let mut stream = TcpStream::connect((host, port)).await?;
+  let request = format!(
+    "GET /.json?{} HTTP/1.1\r\nHost: {}:{}\r\nConnection: close\r\nAccept: application/json\r\n\r\n",
+    percent_encode(query),
+    host,
+    port
+  );
+  stream.write_all(request.as_bytes()).await?;
+  let mut response = Vec::new();
+  stream.read_to_end(&mut response).await?;
+
+  let header_end = response
+    .windows(4)
+    .position(|window| window == b"\r\n\r\n")
+    .ok_or_else(|| broken_json("no HTTP header found"))?;
+  let header = String::from_utf8_lossy(&response[..header_end]);
+  let status = header
+    .split_whitespace()
+    .nth(1)
+    .ok_or_else(|| broken_json("no HTTP status found"))?;
+  if status != "200" {
+    return Err(io::Error::other(format!("HTTP request failed: {}", status)));
+  }
+  let mut body = &response[header_end + 4..];
+
+  // Undo chunked transfer encoding if the server used it.
+  let chunked_body;
+  if header.to_ascii_lowercase().contains("transfer-encoding: chunked") {
+    chunked_body = decode_chunked(body)?;
+    body = &chunked_body;
+  }
+  parse_json(body)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Concatenate the chunks of a chunked HTTP body.
+fn decode_chunked(mut body: &[u8]) -> io::Result<Vec<u8>> {
+  let mut decoded = Vec::new();
+  loop {
+    let line_end = body
+      .windows(2)
+      .position(|window| window == b"\r\n")
+      .ok_or_else(|| broken_json("truncated chunked body"))?;
+    let size = usize::from_str_radix(
+      std::str::from_utf8(&body[..line_end]).map_err(|_| broken_json("invalid chunk size"))?.trim(),
+      16,
+    )
+    .map_err(|_| broken_json("invalid chunk size"))?;
+    body = &body[line_end + 2..];
+    if size == 0 {
+      return Ok(decoded);
+    }
+    if body.len() < size + 2 {
+      return Err(broken_json("truncated chunk"));
+    }
+    decoded.extend_from_slice(&body[..size]);
+    body = &body[size + 2..];
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_scalars() {
+    assert_eq!(parse_json(b"null").unwrap(), Q::Null);
+    assert_eq!(parse_json(b"true").unwrap(), Q::Bool(true));
+    assert_eq!(parse_json(b"42.5").unwrap(), Q::Float(42.5));
+    assert_eq!(
+      parse_json(br#""abc""#).unwrap(),
+      Q::String("abc".to_string())
+    );
+  }
+
+  #[test]
+  fn parse_array_and_object() {
+    assert_eq!(
+      parse_json(b"[1, 2, 3]").unwrap(),
+      Q::MixedList(vec![Q::Float(1.0), Q::Float(2.0), Q::Float(3.0)])
+    );
+    assert_eq!(
+      parse_json(br#"{"a": 1, "b": "x"}"#).unwrap(),
+      Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+        Q::MixedList(vec![Q::Float(1.0), Q::String("x".to_string())]),
+      ))
+    );
+  }
+
+  #[test]
+  fn percent_encodes_query() {
+    assert_eq!(percent_encode("count trade"), "count%20trade");
+    assert_eq!(percent_encode("a+b"), "a%2Bb");
+  }
+
+  #[test]
+  fn decodes_chunked_body() {
+    assert_eq!(
+      decode_chunked(b"4\r\n[1,2\r\n2\r\n,3\r\n1\r\n]\r\n0\r\n\r\n").unwrap(),
+      b"[1,2,3]".to_vec()
+    );
+  }
+}
@@ -0,0 +1,66 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! # rustkdb
+//!
+//! Asynchronous IPC client for q/kdb+.
+//!
+//! The crate is organised in two layers:
+//! - [`qtype`]: Rust representation of q objects ([`qtype::Q`]) together with
+//!   the typed containers [`qtype::QList`], [`qtype::QTable`] and [`qtype::QDictionary`].
+//! - [`connection`]: connection establishment over TCP, TLS and Unix domain
+//!   sockets, synchronous/asynchronous queries and connection pooling.
+//! - [`clients`]: global registry resolving logical names to shared
+//!   connections.
+//! - [`listen`]: server mode accepting inbound kdb+ connections.
+//! - [`convert`]: conversions between [`qtype::Q`] and plain Rust types,
+//!   with `#[derive(QRecord)]` (feature `derive`) mapping structs to
+//!   dictionaries and tables.
+//! - [`query`]: fluent builders for compound-list functional queries.
+//! - [`serde`] (feature `serde`): convert any `Serialize`/`Deserialize`
+//!   type to and from [`qtype::Q`] objects.
+//! - [`blocking`] (feature `blocking`): synchronous client without an
+//!   async runtime.
+//! - [`wasm`] (feature `wasm`, `wasm32` targets): browser WebSocket client.
+//! - [`tick`]: helpers for the kdb+ tick architecture.
+//! - [`testing`]: embedded mock q server for integration tests.
+//!
+//! Serialization to and from the kdb+ IPC wire format is internal to the crate;
+//! users only ever deal with [`qtype::Q`] objects.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Modules                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod clients;
+pub mod connection;
+pub mod convert;
+pub mod http;
+pub mod listen;
+pub mod qtype;
+pub mod query;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod testing;
+pub mod tick;
+pub mod tls;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+
+mod deserialization;
+mod serialization;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Re-exports                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(feature = "derive")]
+pub use rustkdb_derive::QRecord;
+
+// The derive expands paths through `::rustkdb`, which must also resolve in
+//  this crate's own tests.
+#[cfg(test)]
+extern crate self as rustkdb;
@@ -0,0 +1,332 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Server mode: accept inbound kdb+ IPC connections.
+//!
+//! [`Listener`] binds a TCP port, a Unix domain socket or a TLS endpoint,
+//! performs the kdb+ handshake as a server — including a `.z.pw`-style
+//! authentication callback — and hands each accepted connection to the
+//! application as an ordinary [`Handle`]. Combined with
+//! [`Handle::receive_message`](crate::connection::Handle::receive_message) and
+//! [`Handle::send_response`](crate::connection::Handle::send_response) this
+//! lets a Rust service act as a kdb-protocol endpoint which q clients reach
+//! with a plain `hopen`.
+//!
+//! # Example
+//! ```no_run
+//! use rustkdb::listen::Listener;
+//! use rustkdb::qtype::Q;
+//!
+//! # async fn doc() -> std::io::Result<()> {
+//! let listener = Listener::bind("0.0.0.0", 5000)
+//!   .await?
+//!   .auth(|user, password| user == "kdbuser" && password == "pass");
+//! listener
+//!   .serve(|mut handle| async move {
+//!     while let Ok((_message_type, query)) = handle.receive_message().await {
+//!       let _ = handle.send_response(query).await;
+//!     }
+//!   })
+//!   .await
+//! # }
+//! ```
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::io;
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+use crate::connection::{Handle, IpcTransport, CAPABILITY};
+#[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+use crate::tls::TlsIdentity;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Global Variables                   //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Upper bound on the credential part of an inbound handshake, protecting
+///  the listener from rogue clients which never send the null terminator.
+const MAX_CREDENTIAL_BYTES: usize = 1024;
+
+/// Signature of the `.z.pw`-style authentication callback: user and
+///  password of the connecting client, returning `true` to accept.
+type AuthCallback = dyn Fn(&str, &str) -> bool + Send + Sync;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% Listener %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Bound endpoint accepting inbound kdb+ IPC connections.
+pub struct Listener {
+  /// The bound socket.
+  inner: Inner,
+  /// Optional `.z.pw`-style authentication callback.
+  auth: Option<Arc<AuthCallback>>,
+}
+
+/// The bound socket of a [`Listener`].
+enum Inner {
+  /// Plain TCP.
+  Tcp(TcpListener),
+  /// Unix domain socket.
+  #[cfg(unix)]
+  Uds(UnixListener),
+  /// TCP wrapped in server-side TLS.
+  #[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+  Tls {
+    /// The bound TCP socket.
+    listener: TcpListener,
+    /// Acceptor presenting the server identity.
+    acceptor: crate::tls::TlsAcceptor,
+  },
+}
+
+impl Listener {
+  /// Bind a plain TCP listener.
+  /// # Parameters
+  /// - `host`: Interface to bind, e.g. `"0.0.0.0"`.
+  /// - `port`: Port to bind. `0` picks a free port; see [`local_port`](Listener::local_port).
+  pub async fn bind(host: &str, port: u16) -> io::Result<Listener> {
+    let listener = TcpListener::bind((host, port)).await?;
+    Ok(Listener {
+      inner: Inner::Tcp(listener),
+      auth: None,
+    })
+  }
+
+  /// Bind a Unix domain socket listener at the given filesystem path.
+  #[cfg(unix)]
+  pub fn bind_uds(path: &str) -> io::Result<Listener> {
+    let listener = UnixListener::bind(path)?;
+    Ok(Listener {
+      inner: Inner::Uds(listener),
+      auth: None,
+    })
+  }
+
+  /// Bind a TLS listener presenting the given identity, the counterpart of
+  ///  a q client connecting with `hopen` over TLS.
+  /// # Parameters
+  /// - `host`: Interface to bind.
+  /// - `port`: Port to bind.
+  /// - `identity`: Server certificate and private key.
+  #[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+  pub async fn bind_tls(host: &str, port: u16, identity: &TlsIdentity) -> io::Result<Listener> {
+    let acceptor = crate::tls::build_acceptor(identity)?;
+    let listener = TcpListener::bind((host, port)).await?;
+    Ok(Listener {
+      inner: Inner::Tls { listener, acceptor },
+      auth: None,
+    })
+  }
+
+  /// Install a `.z.pw`-style authentication callback receiving the user and
+  ///  password of each connecting client. When the callback returns `false`
+  ///  the connection is closed without a handshake reply, which clients see
+  ///  as access denied — exactly like q running with `-u`.
+  pub fn auth<F>(mut self, callback: F) -> Self
+  where
+    F: Fn(&str, &str) -> bool + Send + Sync + 'static,
+  {
+    self.auth = Some(Arc::new(callback));
+    self
+  }
+
+  /// Port the listener is bound to, useful after binding port `0`.
+  /// # Note
+  /// Fails for Unix domain socket listeners, which have no port.
+  pub fn local_port(&self) -> io::Result<u16> {
+    match &self.inner {
+      Inner::Tcp(listener) => Ok(listener.local_addr()?.port()),
+      #[cfg(unix)]
+      Inner::Uds(_) => Err(io::Error::other("a Unix domain socket listener has no port")),
+      #[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+      Inner::Tls { listener, .. } => Ok(listener.local_addr()?.port()),
+    }
+  }
+
+  /// Accept one inbound connection and complete the server-side handshake.
+  ///
+  /// The returned handle starts with empty statistics and no timeouts; use
+  ///  [`Handle::receive_message`](crate::connection::Handle::receive_message)
+  ///  to read queries and
+  ///  [`Handle::send_response`](crate::connection::Handle::send_response) to
+  ///  answer synchronous ones.
+  pub async fn accept(&self) -> io::Result<Handle> {
+    let stream = self.accept_transport().await?;
+    self.handshake_accepted(stream).await
+  }
+
+  /// Accept connections forever, spawning the handler once per connection.
+  ///
+  /// Connections failing the handshake — bad credentials, oversized
+  ///  greeting, broken TLS negotiation — are dropped without stopping the
+  ///  listener; only listener-level failures end the loop.
+  pub async fn serve<H, F>(self, handler: H) -> io::Result<()>
+  where
+    H: Fn(Handle) -> F + Send + Sync + 'static,
+    F: std::future::Future<Output = ()> + Send + 'static,
+  {
+    let handler = Arc::new(handler);
+    loop {
+      let stream = self.accept_transport().await?;
+      match self.handshake_accepted(stream).await {
+        Ok(handle) => {
+          let handler = Arc::clone(&handler);
+          tokio::spawn(async move { handler(handle).await });
+        }
+        Err(_error) => {
+          #[cfg(feature = "tracing")]
+          tracing::debug!(target: "rustkdb::listen", error = %_error, "inbound handshake failed");
+        }
+      }
+    }
+  }
+
+  /// Accept the next connection at the transport level. Errors here are
+  ///  listener failures, not failures of an individual client.
+  async fn accept_transport(&self) -> io::Result<Box<dyn IpcTransport>> {
+    match &self.inner {
+      Inner::Tcp(listener) => {
+        let (stream, _peer) = listener.accept().await?;
+        stream.set_nodelay(true)?;
+        Ok(Box::new(stream))
+      }
+      #[cfg(unix)]
+      Inner::Uds(listener) => {
+        let (stream, _peer) = listener.accept().await?;
+        Ok(Box::new(stream))
+      }
+      #[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+      Inner::Tls { listener, acceptor } => {
+        let (stream, _peer) = listener.accept().await?;
+        stream.set_nodelay(true)?;
+        let tls = crate::tls::accept_tls_stream(acceptor, stream).await?;
+        Ok(Box::new(tls))
+      }
+    }
+  }
+
+  /// Run the server-side handshake on an accepted transport and wrap it
+  ///  into a handle.
+  async fn handshake_accepted(&self, mut stream: Box<dyn IpcTransport>) -> io::Result<Handle> {
+    let capability = server_handshake(stream.as_mut(), self.auth.as_deref()).await?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(target: "rustkdb::listen", capability, "inbound handshake complete");
+    Ok(Handle::accepted(stream, capability))
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Perform the kdb+ handshake as a server: read `user:password`, an
+///  optional capability byte and the null terminator, authenticate, then
+///  reply with the capability in effect. On rejection the error propagates
+///  and the connection is simply dropped, so the client sees the same
+///  silence as from q running with `-u`.
+async fn server_handshake<S>(stream: &mut S, auth: Option<&AuthCallback>) -> io::Result<u8>
+where
+  S: IpcTransport + ?Sized,
+{
+  let mut greeting = Vec::new();
+  let mut byte = [0_u8; 1];
+  loop {
+    stream.read_exact(&mut byte).await?;
+    if byte[0] == 0 {
+      break;
+    }
+    if greeting.len() == MAX_CREDENTIAL_BYTES {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "inbound handshake credential exceeds 1024 bytes",
+      ));
+    }
+    greeting.push(byte[0]);
+  }
+  // A trailing byte below ASCII space is the requested IPC capability, not
+  // part of the credential text.
+  let requested = match greeting.last() {
+    Some(&last) if last < b' ' => {
+      greeting.pop();
+      last
+    }
+    _ => 0,
+  };
+  let credential = String::from_utf8_lossy(&greeting).into_owned();
+  let (user, password) = credential.split_once(':').unwrap_or((credential.as_str(), ""));
+  if let Some(callback) = auth {
+    if !callback(user, password) {
+      return Err(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("access denied for user '{}'", user),
+      ));
+    }
+  }
+  let capability = requested.min(CAPABILITY);
+  stream.write_all(&[capability]).await?;
+  Ok(capability)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::connection::{connect, MessageType};
+  use crate::qtype::Q;
+
+  #[tokio::test]
+  async fn accepted_connection_serves_sync_queries() {
+    let listener = Listener::bind("127.0.0.1", 0)
+      .await
+      .unwrap()
+      .auth(|user, password| user == "kdbuser" && password == "pass");
+    let port = listener.local_port().unwrap();
+    let server = tokio::spawn(async move {
+      let mut handle = listener.accept().await.unwrap();
+      let (message_type, query) = handle.receive_message().await.unwrap();
+      assert_eq!(message_type, MessageType::Sync);
+      assert_eq!(query, Q::Symbol("ping".to_string()));
+      handle.send_response(Q::Long(42)).await.unwrap();
+    });
+    let mut client = connect("127.0.0.1", port, "kdbuser:pass", 1000, 0)
+      .await
+      .unwrap();
+    let answer = client.send_query(Q::Symbol("ping".to_string())).await.unwrap();
+    assert_eq!(answer, Q::Long(42));
+    server.await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn rejected_credentials_close_the_connection() {
+    let listener = Listener::bind("127.0.0.1", 0)
+      .await
+      .unwrap()
+      .auth(|_user, _password| false);
+    let port = listener.local_port().unwrap();
+    let server = tokio::spawn(async move {
+      let error = listener.accept().await.err().expect("the handshake must fail");
+      assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    });
+    let error = connect("127.0.0.1", port, "mallory:guess", 1000, 0)
+      .await
+      .err()
+      .expect("the client handshake must fail");
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    server.await.unwrap();
+  }
+}
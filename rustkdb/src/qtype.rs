@@ -0,0 +1,3331 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Rust representation of q objects.
+//!
+//! Atoms are stored in their raw q representation, i.e. temporal types keep
+//! the underlying integer offset from the q epoch (2000.01.01) rather than
+//! being eagerly converted into calendar types. Lists carry an optional
+//! attribute (`` `s#``, `` `u#``, `` `p#``, `` `g#``) alongside their data.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::Arc;
+
+use crate::convert::{FromQ, IntoQ};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Global Variables                   //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// q type code of a mixed list.
+pub(crate) const Q_MIXED_LIST: i8 = 0;
+/// q type code of a table.
+pub(crate) const Q_TABLE: i8 = 98;
+/// q type code of a dictionary.
+pub(crate) const Q_DICTIONARY: i8 = 99;
+/// q type code of a sorted dictionary, i.e. a dictionary or keyed table
+///  whose keys carry the `` `s#`` attribute.
+pub(crate) const Q_SORTED_DICTIONARY: i8 = 127;
+/// q type code of a lambda.
+pub(crate) const Q_LAMBDA: i8 = 100;
+/// q type code of an operator.
+pub(crate) const Q_OPERATOR: i8 = 102;
+/// q type code of an iterator.
+pub(crate) const Q_ITERATOR: i8 = 103;
+/// q type code of a projection.
+pub(crate) const Q_PROJECTION: i8 = 104;
+/// q type code of a composition.
+pub(crate) const Q_COMPOSITION: i8 = 105;
+/// Smallest q type code of an enumerated symbol list, i.e. the `sym` domain.
+pub(crate) const Q_ENUM_MIN: i8 = 20;
+/// Largest q type code of an enumerated symbol list.
+pub(crate) const Q_ENUM_MAX: i8 = 76;
+/// q type code of the generic null `(::)`.
+pub(crate) const Q_GENERAL_NULL: i8 = 101;
+/// q type code of an error response.
+pub(crate) const Q_ERROR: i8 = -128;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% Attribute %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Attribute attached to a q list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attribute {
+  /// No attribute.
+  None,
+  /// Sorted attribute, i.e. `` `s# ``.
+  Sorted,
+  /// Unique attribute, i.e. `` `u# ``.
+  Unique,
+  /// Parted attribute, i.e. `` `p# ``.
+  Parted,
+  /// Grouped attribute, i.e. `` `g# ``.
+  Grouped,
+}
+
+impl Attribute {
+  /// Convert the attribute into its wire representation.
+  pub(crate) fn to_byte(self) -> u8 {
+    match self {
+      Attribute::None => 0,
+      Attribute::Sorted => 1,
+      Attribute::Unique => 2,
+      Attribute::Parted => 3,
+      Attribute::Grouped => 4,
+    }
+  }
+
+  /// Restore an attribute from its wire representation. Unknown values fall
+  ///  back to `None` rather than failing the whole message.
+  pub(crate) fn from_byte(byte: u8) -> Self {
+    match byte {
+      1 => Attribute::Sorted,
+      2 => Attribute::Unique,
+      3 => Attribute::Parted,
+      4 => Attribute::Grouped,
+      _ => Attribute::None,
+    }
+  }
+}
+
+//%% QList %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Simple (homogeneous) q list together with its attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QList<T> {
+  /// Attribute of the list.
+  attribute: Attribute,
+  /// Underlying elements.
+  data: Vec<T>,
+}
+
+impl<T> QList<T> {
+  /// Construct a list without an attribute.
+  pub fn new(data: Vec<T>) -> Self {
+    QList {
+      attribute: Attribute::None,
+      data,
+    }
+  }
+
+  /// Construct a list carrying the given attribute.
+  pub fn with_attribute(data: Vec<T>, attribute: Attribute) -> Self {
+    QList { attribute, data }
+  }
+
+  /// Attribute of the list.
+  pub fn attribute(&self) -> Attribute {
+    self.attribute
+  }
+
+  /// Set the attribute of the list.
+  pub fn set_attribute(&mut self, attribute: Attribute) {
+    self.attribute = attribute;
+  }
+
+  /// Borrow the underlying elements.
+  pub fn data(&self) -> &[T] {
+    &self.data
+  }
+
+  /// Iterate over references to the elements.
+  pub fn iter(&self) -> std::slice::Iter<'_, T> {
+    self.data.iter()
+  }
+
+  /// Mutably borrow the underlying elements.
+  pub fn data_mut(&mut self) -> &mut Vec<T> {
+    &mut self.data
+  }
+
+  /// Consume the list and return the underlying elements.
+  pub fn into_data(self) -> Vec<T> {
+    self.data
+  }
+
+  /// The number of elements in the list.
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  /// `true` if the list has no elements.
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  /// Append an element to the list.
+  pub fn push(&mut self, item: T) {
+    self.data.push(item);
+  }
+}
+
+impl<T: PartialOrd> QList<T> {
+  /// Sort the elements ascending in place and set the `` `s#`` attribute,
+  ///  mirroring `asc`. NaN floats sort first, like q nulls.
+  pub fn sort(&mut self) {
+    self
+      .data
+      .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    self.attribute = Attribute::Sorted;
+  }
+}
+
+impl<T> IntoIterator for QList<T> {
+  type Item = T;
+  type IntoIter = std::vec::IntoIter<T>;
+
+  /// Iterate over the elements by value, dropping the attribute.
+  fn into_iter(self) -> Self::IntoIter {
+    self.data.into_iter()
+  }
+}
+
+impl<'a, T> IntoIterator for &'a QList<T> {
+  type Item = &'a T;
+  type IntoIter = std::slice::Iter<'a, T>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.data.iter()
+  }
+}
+
+//%% QTable %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// q table, i.e. a flipped dictionary of column names and column values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QTable {
+  /// Column names.
+  columns: Vec<String>,
+  /// Column values. Each element is a list `Q` object of the same length.
+  values: Vec<Q>,
+}
+
+impl QTable {
+  /// Construct a table from column names and column values.
+  /// # Note
+  /// The number of column names must match the number of column values.
+  pub fn new(columns: Vec<String>, values: Vec<Q>) -> io::Result<Self> {
+    if columns.len() != values.len() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "length of columns does not match length of values",
+      ));
+    }
+    Ok(QTable { columns, values })
+  }
+
+  /// Column names of the table.
+  pub fn columns(&self) -> &[String] {
+    &self.columns
+  }
+
+  /// Column values of the table.
+  pub fn values(&self) -> &[Q] {
+    &self.values
+  }
+
+  /// Decompose the table into column names and column values.
+  pub fn into_parts(self) -> (Vec<String>, Vec<Q>) {
+    (self.columns, self.values)
+  }
+
+  /// Append a column to the table.
+  /// # Parameters
+  /// - `name`: Name of the new column. Must not collide with an existing
+  ///   column.
+  /// - `values`: Value list of the column. Must be a list of the same
+  ///   length as the existing columns.
+  pub fn add_column(&mut self, name: &str, values: Q) -> io::Result<()> {
+    if self.columns.iter().any(|column| column == name) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("the table already has a column '{}'", name),
+      ));
+    }
+    if !values.is_list() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "table column is not a list",
+      ));
+    }
+    if !self.columns.is_empty() && values.len() != self.row_count() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "length of column does not match the row count",
+      ));
+    }
+    self.columns.push(name.to_string());
+    self.values.push(values);
+    Ok(())
+  }
+
+  /// Remove a column from the table, returning its value list.
+  /// # Parameters
+  /// - `name`: Name of the column to remove.
+  pub fn drop_column(&mut self, name: &str) -> io::Result<Q> {
+    let position = column_position(&self.columns, name)?;
+    self.columns.remove(position);
+    Ok(self.values.remove(position))
+  }
+
+  /// Rename a column, keeping its values and position.
+  /// # Parameters
+  /// - `old`: Current name of the column.
+  /// - `new`: New name. Must not collide with another column.
+  pub fn rename_column(&mut self, old: &str, new: &str) -> io::Result<()> {
+    let position = column_position(&self.columns, old)?;
+    if old != new && self.columns.iter().any(|column| column == new) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("the table already has a column '{}'", new),
+      ));
+    }
+    self.columns[position] = new.to_string();
+    Ok(())
+  }
+
+  /// Append one record to the table.
+  /// # Parameters
+  /// - `row`: Value of each column as an atom, in column order. A mixed
+  ///   list column accepts any object.
+  pub fn push_row(&mut self, row: Vec<Q>) -> io::Result<()> {
+    if row.len() != self.columns.len() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "length of row does not match the number of columns",
+      ));
+    }
+    // Validate every value before touching the table, so a failed push
+    //  does not leave columns of different lengths behind.
+    for (column, atom) in self.values.iter().zip(&row) {
+      let matches = match (column, atom) {
+        (Q::MixedList(_), _) => true,
+        (Q::String(_), Q::Char(_)) => true,
+        (column, atom) => column.q_type() == -atom.q_type(),
+      };
+      if !matches {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          format!(
+            "cannot append a q {} to a q {} column",
+            crate::convert::q_type_name(atom),
+            crate::convert::q_type_name(column)
+          ),
+        ));
+      }
+    }
+    for (column, atom) in self.values.iter_mut().zip(row) {
+      push_column_atom(column, atom)?;
+    }
+    Ok(())
+  }
+
+  /// Append every row of another table with the same schema, i.e. the same
+  ///  column names in the same order holding the same column types. Any
+  ///  list attributes of the affected columns are cleared.
+  /// # Parameters
+  /// - `other`: Table to append.
+  pub fn concat(&mut self, other: QTable) -> io::Result<()> {
+    if self.columns != other.columns {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "tables have different columns",
+      ));
+    }
+    for (column, addition) in self.values.iter().zip(&other.values) {
+      if column.q_type() != addition.q_type() {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          format!(
+            "cannot concatenate a q {} column onto a q {} column",
+            crate::convert::q_type_name(addition),
+            crate::convert::q_type_name(column)
+          ),
+        ));
+      }
+    }
+    for (column, addition) in self.values.iter_mut().zip(other.values) {
+      concat_column(column, addition)?;
+    }
+    Ok(())
+  }
+
+  /// Sort the rows of the table ascending by the named column in place,
+  ///  mirroring `xasc`: the sort is stable, the key column gains the
+  ///  `` `s#`` attribute and the other columns lose any attribute, as the
+  ///  reordering may no longer respect them.
+  /// # Parameters
+  /// - `column`: Name of the column to sort by.
+  pub fn sort_by(&mut self, column: &str) -> io::Result<()> {
+    let position = column_position(&self.columns, column)?;
+    let key = &self.values[position];
+    let mut order: Vec<usize> = (0..self.row_count()).collect();
+    let atoms: Vec<Q> = order
+      .iter()
+      .map(|&index| key.get(index).unwrap_or(Q::Null))
+      .collect();
+    let mut unsupported = false;
+    order.sort_by(|&a, &b| match compare_atoms(&atoms[a], &atoms[b]) {
+      Some(ordering) => ordering,
+      None => {
+        unsupported = true;
+        std::cmp::Ordering::Equal
+      }
+    });
+    if unsupported {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "cannot sort by column '{}' of q type {}",
+          column,
+          crate::convert::q_type_name(key)
+        ),
+      ));
+    }
+    for (index, value) in self.values.iter_mut().enumerate() {
+      let attribute = if index == position {
+        Attribute::Sorted
+      } else {
+        Attribute::None
+      };
+      *value = apply_order(value, &order, attribute)?;
+    }
+    Ok(())
+  }
+
+  /// Left join with a keyed table, like `lj` in q: every row of this
+  ///  table is kept and enriched with the value columns of the first
+  ///  matching row of `right`, matched on the key columns of `right` by
+  ///  hash lookup. A value column that already exists in this table is
+  ///  overwritten where a match is found; unmatched rows keep their left
+  ///  values, or hold typed nulls in columns new to this table.
+  /// # Parameters
+  /// - `right`: Keyed reference table. All of its key columns must exist
+  ///   in this table.
+  pub fn lj(&self, right: &QKeyedTable) -> io::Result<QTable> {
+    self.join(right, false)
+  }
+
+  /// Inner join with a keyed table, like `ij` in q: only the rows of this
+  ///  table whose key is present in `right` are kept, enriched with the
+  ///  value columns of the first matching row.
+  /// # Parameters
+  /// - `right`: Keyed reference table. All of its key columns must exist
+  ///   in this table.
+  pub fn ij(&self, right: &QKeyedTable) -> io::Result<QTable> {
+    self.join(right, true)
+  }
+
+  /// Hash join with a keyed table, keeping every left row (`lj`) or only
+  ///  the matched ones (`ij`).
+  fn join(&self, right: &QKeyedTable, inner: bool) -> io::Result<QTable> {
+    let left_positions = right
+      .keys()
+      .columns()
+      .iter()
+      .map(|name| column_position(&self.columns, name))
+      .collect::<io::Result<Vec<usize>>>()?;
+    // Hash the key rows of the right table. The first row wins for a
+    //  duplicate key, as in q.
+    let mut lookup = HashMap::with_capacity(right.row_count());
+    for row in 0..right.row_count() {
+      let mut key = Vec::new();
+      for column in right.keys().values() {
+        push_key_atom(&column.get(row).unwrap_or(Q::Null), &mut key)?;
+      }
+      lookup.entry(key).or_insert(row);
+    }
+    let mut matches = Vec::with_capacity(self.row_count());
+    for row in 0..self.row_count() {
+      let mut key = Vec::new();
+      for &position in &left_positions {
+        push_key_atom(&self.values[position].get(row).unwrap_or(Q::Null), &mut key)?;
+      }
+      matches.push(lookup.get(&key).copied());
+    }
+    let mut result = if inner {
+      let kept = matches
+        .iter()
+        .enumerate()
+        .filter_map(|(row, matched)| matched.map(|_| row))
+        .collect::<Vec<usize>>();
+      matches.retain(Option::is_some);
+      self.select_rows(&kept)?
+    } else {
+      self.clone()
+    };
+    enrich_columns(&mut result, right.values(), &matches, &[])?;
+    Ok(result)
+  }
+
+  /// Asof join, like `aj` in q: every row of this table is kept and
+  ///  enriched with the non-key columns of the `right` row holding the
+  ///  same values in the leading key columns and the greatest value in the
+  ///  last key column that does not exceed this table's value — the usual
+  ///  "prevailing quote for each trade" match. Rows with no such match
+  ///  hold typed nulls in the joined columns.
+  /// # Parameters
+  /// - `columns`: Names of the key columns, which must exist in both
+  ///   tables. All but the last are matched exactly; the last (typically
+  ///   a time column) is matched as-of.
+  /// - `right`: Reference table, e.g. a quote table. Within each exact-key
+  ///   group its rows are considered in ascending order of the as-of
+  ///   column regardless of their physical order.
+  pub fn aj(&self, columns: &[&str], right: &QTable) -> io::Result<QTable> {
+    let Some((&asof, exact)) = columns.split_last() else {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "an asof join needs at least one key column",
+      ));
+    };
+    let left_exact = exact
+      .iter()
+      .map(|name| column_position(&self.columns, name))
+      .collect::<io::Result<Vec<usize>>>()?;
+    let right_exact = exact
+      .iter()
+      .map(|name| column_position(&right.columns, name))
+      .collect::<io::Result<Vec<usize>>>()?;
+    let left_asof = column_position(&self.columns, asof)?;
+    let right_asof = column_position(&right.columns, asof)?;
+    // Group the right rows by exact key and sort each group by the as-of
+    //  value, so each left row resolves with a binary search.
+    let mut groups: HashMap<Vec<u8>, Vec<(Q, usize)>> = HashMap::new();
+    for row in 0..right.row_count() {
+      let mut key = Vec::new();
+      for &position in &right_exact {
+        push_key_atom(&right.values[position].get(row).unwrap_or(Q::Null), &mut key)?;
+      }
+      let value = right.values[right_asof].get(row).unwrap_or(Q::Null);
+      groups.entry(key).or_default().push((value, row));
+    }
+    for group in groups.values_mut() {
+      group.sort_by(|(a, _), (b, _)| {
+        compare_atoms(a, b).unwrap_or(std::cmp::Ordering::Equal)
+      });
+    }
+    let mut matches = Vec::with_capacity(self.row_count());
+    for row in 0..self.row_count() {
+      let mut key = Vec::new();
+      for &position in &left_exact {
+        push_key_atom(&self.values[position].get(row).unwrap_or(Q::Null), &mut key)?;
+      }
+      let target = self.values[left_asof].get(row).unwrap_or(Q::Null);
+      matches.push(groups.get(&key).and_then(|group| {
+        let position = group.partition_point(|(value, _)| {
+          compare_atoms(value, &target)
+            .is_some_and(|ordering| ordering != std::cmp::Ordering::Greater)
+        });
+        position.checked_sub(1).map(|index| group[index].1)
+      }));
+    }
+    let mut result = self.clone();
+    let skip = columns
+      .iter()
+      .map(|name| name.to_string())
+      .collect::<Vec<String>>();
+    enrich_columns(&mut result, right, &matches, &skip)?;
+    Ok(result)
+  }
+
+  /// Truncate a temporal column to bar boundaries in place, like
+  ///  `` update time: interval xbar time from table`` in q, e.g. as the
+  ///  first step of building bars locally.
+  /// # Parameters
+  /// - `column`: Name of the column to truncate. Must be a timestamp,
+  ///   timespan, time, second, minute or date list.
+  /// - `interval`: Width of a bar. Must be a positive whole number of the
+  ///   column's unit, e.g. whole milliseconds for a time column.
+  pub fn bucket_by_time(&mut self, column: &str, interval: std::time::Duration) -> io::Result<()> {
+    let position = column_position(&self.columns, column)?;
+    let values = &self.values[position];
+    let interval = match values {
+      Q::TimestampList(_) | Q::TimespanList(_) => i64::try_from(interval.as_nanos()),
+      Q::TimeList(_) => i64::try_from(interval.as_millis()),
+      Q::SecondList(_) => i64::try_from(interval.as_secs()),
+      Q::MinuteList(_) => i64::try_from(interval.as_secs() / 60),
+      Q::DateList(_) => i64::try_from(interval.as_secs() / 86_400),
+      other => {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidData,
+          format!(
+            "column '{}' is a q {}, not a temporal list",
+            column,
+            crate::convert::q_type_name(other)
+          ),
+        ))
+      }
+    }
+    .map_err(|_| {
+      io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "xbar interval does not fit the value type",
+      )
+    })?;
+    self.values[position] = values.xbar(interval)?;
+    Ok(())
+  }
+
+  /// Schema of the table: the column names and element types, in order.
+  pub fn schema(&self) -> QSchema {
+    QSchema {
+      columns: self
+        .columns
+        .iter()
+        .zip(&self.values)
+        .map(|(name, value)| {
+          (
+            name.clone(),
+            QType::from_type_code(value.q_type()).unwrap_or(QType::Mixed),
+          )
+        })
+        .collect(),
+    }
+  }
+
+  /// Build a new table holding the rows the predicate keeps, in their
+  ///  original order. Column attributes are preserved, as dropping rows
+  ///  does not invalidate them.
+  /// # Example
+  /// ```no_run
+  /// # fn big(table: &rustkdb::qtype::QTable) -> std::io::Result<rustkdb::qtype::QTable> {
+  /// table.filter(|row| row.get::<f64>("price").is_ok_and(|price| price > 100.0))
+  /// # }
+  /// ```
+  pub fn filter<F>(&self, mut predicate: F) -> io::Result<QTable>
+  where
+    F: FnMut(&Row<'_>) -> bool,
+  {
+    let kept: Vec<usize> = self
+      .rows()
+      .filter(|row| predicate(row))
+      .map(|row| row.index())
+      .collect();
+    self.select_rows(&kept)
+  }
+
+  /// Build a new table holding at most the first `n` rows.
+  pub fn take(&self, n: usize) -> io::Result<QTable> {
+    self.slice(0..n.min(self.row_count()))
+  }
+
+  /// Build a new table holding the rows of the given index range.
+  /// # Parameters
+  /// - `range`: Row range; must not reach beyond the end of the table.
+  pub fn slice(&self, range: std::ops::Range<usize>) -> io::Result<QTable> {
+    if range.end > self.row_count() || range.start > range.end {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "row range out of range",
+      ));
+    }
+    self.select_rows(&range.collect::<Vec<usize>>())
+  }
+
+  /// Build a new table from the rows at the given indices, keeping each
+  ///  column's attribute.
+  fn select_rows(&self, order: &[usize]) -> io::Result<QTable> {
+    let values = self
+      .values
+      .iter()
+      .map(|value| apply_order(value, order, column_attribute(value)))
+      .collect::<io::Result<Vec<Q>>>()?;
+    Ok(QTable {
+      columns: self.columns.clone(),
+      values,
+    })
+  }
+
+  /// Value list of the named column, or `None` if the table has no such
+  ///  column.
+  /// # Parameters
+  /// - `name`: Column name.
+  pub fn column(&self, name: &str) -> Option<&Q> {
+    let position = self.columns.iter().position(|column| column == name)?;
+    Some(&self.values[position])
+  }
+
+  /// Mutable value list of the named column, e.g. to amend values before
+  ///  sending the table back.
+  /// # Parameters
+  /// - `name`: Column name.
+  pub fn column_mut(&mut self, name: &str) -> Option<&mut Q> {
+    let position = self.columns.iter().position(|column| column == name)?;
+    Some(&mut self.values[position])
+  }
+
+  /// Elements of the named column as a slice of ints, failing when the
+  ///  column is missing or of another type.
+  /// # Parameters
+  /// - `name`: Column name.
+  pub fn column_i32(&self, name: &str) -> io::Result<&[i32]> {
+    match &self.values[column_position(&self.columns, name)?] {
+      Q::IntList(list) => Ok(list.data()),
+      other => Err(column_type_mismatch(name, other, "int")),
+    }
+  }
+
+  /// Elements of the named column as a slice of longs, failing when the
+  ///  column is missing or of another type.
+  /// # Parameters
+  /// - `name`: Column name.
+  pub fn column_i64(&self, name: &str) -> io::Result<&[i64]> {
+    match &self.values[column_position(&self.columns, name)?] {
+      Q::LongList(list) => Ok(list.data()),
+      other => Err(column_type_mismatch(name, other, "long")),
+    }
+  }
+
+  /// Elements of the named column as a slice of floats, failing when the
+  ///  column is missing or of another type.
+  /// # Parameters
+  /// - `name`: Column name.
+  pub fn column_f64(&self, name: &str) -> io::Result<&[f64]> {
+    match &self.values[column_position(&self.columns, name)?] {
+      Q::FloatList(list) => Ok(list.data()),
+      other => Err(column_type_mismatch(name, other, "float")),
+    }
+  }
+
+  /// Elements of the named column as a slice of symbols, failing when the
+  ///  column is missing or of another type.
+  /// # Parameters
+  /// - `name`: Column name.
+  pub fn column_sym(&self, name: &str) -> io::Result<&[String]> {
+    match &self.values[column_position(&self.columns, name)?] {
+      Q::SymbolList(list) => Ok(list.data()),
+      other => Err(column_type_mismatch(name, other, "symbol")),
+    }
+  }
+
+  /// Number of rows in the table.
+  pub fn row_count(&self) -> usize {
+    self.values.first().map_or(0, Q::len)
+  }
+
+  /// Iterate over the table record by record, each row accessing its
+  ///  values by column name.
+  /// # Example
+  /// ```no_run
+  /// # fn show(table: &rustkdb::qtype::QTable) -> std::io::Result<()> {
+  /// for row in table.rows() {
+  ///   println!("{} {}", row.get::<String>("sym")?, row.get::<f64>("price")?);
+  /// }
+  /// # Ok(())}
+  /// ```
+  pub fn rows(&self) -> Rows<'_> {
+    Rows {
+      table: self,
+      index: 0,
+      count: self.row_count(),
+    }
+  }
+
+  /// Convert the table into strongly typed records, one per row. Works
+  ///  with any type whose [`FromQ`] implementation reads a table, i.e.
+  ///  `#[derive(QRecord)]` types (with the `derive` cargo feature).
+  /// # Example
+  /// ```ignore
+  /// let trades = table.rows_as::<Trade>()?;
+  /// ```
+  pub fn rows_as<T: FromQ>(self) -> io::Result<Vec<T>> {
+    T::vec_from_q(Q::Table(self))
+  }
+
+  /// Consume the table and iterate over owned rows.
+  pub fn into_rows(self) -> IntoRows {
+    let count = self.row_count();
+    IntoRows {
+      columns: Arc::new(self.columns),
+      values: self.values,
+      index: 0,
+      count,
+    }
+  }
+}
+
+//%% Row %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Borrowed view of one table row, accessing values by column name. Built
+///  by [`QTable::rows`].
+pub struct Row<'a> {
+  /// Table the row belongs to.
+  table: &'a QTable,
+  /// Row index within the table.
+  index: usize,
+}
+
+impl Row<'_> {
+  /// Row index within the table.
+  pub fn index(&self) -> usize {
+    self.index
+  }
+
+  /// Value of the named column in this row, converted into the requested
+  ///  Rust type.
+  /// # Parameters
+  /// - `column`: Column name.
+  pub fn get<T: FromQ>(&self, column: &str) -> io::Result<T> {
+    let position = column_position(self.table.columns(), column)?;
+    let atom = self.table.values[position]
+      .get(self.index)
+      .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "row index out of range"))?;
+    T::from_q(atom)
+  }
+}
+
+/// Iterator over the rows of a borrowed table. Built by [`QTable::rows`].
+pub struct Rows<'a> {
+  /// Table being iterated.
+  table: &'a QTable,
+  /// Next row index.
+  index: usize,
+  /// Number of rows in the table.
+  count: usize,
+}
+
+impl<'a> Iterator for Rows<'a> {
+  type Item = Row<'a>;
+
+  fn next(&mut self) -> Option<Row<'a>> {
+    if self.index >= self.count {
+      return None;
+    }
+    let row = Row {
+      table: self.table,
+      index: self.index,
+    };
+    self.index += 1;
+    Some(row)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = self.count - self.index;
+    (remaining, Some(remaining))
+  }
+}
+
+impl ExactSizeIterator for Rows<'_> {}
+
+/// Owned row of a table: the column names, shared across the rows of one
+///  iteration, together with the values of one record as atoms.
+pub struct OwnedRow {
+  /// Column names of the table the row came from.
+  columns: Arc<Vec<String>>,
+  /// Value of each column in this row, in column order.
+  values: Vec<Q>,
+}
+
+impl OwnedRow {
+  /// Value of the named column in this row, converted into the requested
+  ///  Rust type.
+  /// # Parameters
+  /// - `column`: Column name.
+  pub fn get<T: FromQ>(&self, column: &str) -> io::Result<T> {
+    let position = column_position(&self.columns, column)?;
+    T::from_q(self.values[position].clone())
+  }
+
+  /// Consume the row and return its values in column order.
+  pub fn into_values(self) -> Vec<Q> {
+    self.values
+  }
+}
+
+/// Iterator over the rows of an owned table. Built by
+///  [`QTable::into_rows`].
+pub struct IntoRows {
+  /// Column names, shared with every yielded row.
+  columns: Arc<Vec<String>>,
+  /// Column values of the table.
+  values: Vec<Q>,
+  /// Next row index.
+  index: usize,
+  /// Number of rows in the table.
+  count: usize,
+}
+
+impl Iterator for IntoRows {
+  type Item = OwnedRow;
+
+  fn next(&mut self) -> Option<OwnedRow> {
+    if self.index >= self.count {
+      return None;
+    }
+    let values = self
+      .values
+      .iter()
+      .map(|column| column.get(self.index).unwrap_or(Q::Null))
+      .collect();
+    self.index += 1;
+    Some(OwnedRow {
+      columns: Arc::clone(&self.columns),
+      values,
+    })
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = self.count - self.index;
+    (remaining, Some(remaining))
+  }
+}
+
+impl ExactSizeIterator for IntoRows {}
+
+//%% QKeyedTable %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// q keyed table, i.e. a dictionary mapping a key table to a value table,
+///  as `xkey` builds in q. On the wire a keyed table travels as a
+///  dictionary of two tables (type 99h or 127h).
+#[derive(Clone, Debug, PartialEq)]
+pub struct QKeyedTable {
+  /// Key table. Holds the key columns.
+  keys: QTable,
+  /// Value table. Holds the value columns, with the same number of rows
+  ///  as the key table.
+  values: QTable,
+}
+
+impl QKeyedTable {
+  /// Construct a keyed table from a key table and a value table.
+  /// # Note
+  /// The key table must have at least one column, and the two tables must
+  ///  have the same number of rows.
+  pub fn new(keys: QTable, values: QTable) -> io::Result<Self> {
+    if keys.columns().is_empty() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "a keyed table needs at least one key column",
+      ));
+    }
+    if !values.columns().is_empty() && keys.row_count() != values.row_count() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "length of keys does not match length of values",
+      ));
+    }
+    Ok(QKeyedTable { keys, values })
+  }
+
+  /// Key a plain table on the named columns, like `` `sym xkey table`` in
+  ///  q: the named columns become the key table and the remaining columns
+  ///  become the value table.
+  /// # Parameters
+  /// - `table`: Table to key.
+  /// - `keys`: Names of the key columns. Must all exist in the table.
+  pub fn xkey(mut table: QTable, keys: &[&str]) -> io::Result<Self> {
+    let mut key_columns = Vec::with_capacity(keys.len());
+    let mut key_values = Vec::with_capacity(keys.len());
+    for &name in keys {
+      key_values.push(table.drop_column(name)?);
+      key_columns.push(name.to_string());
+    }
+    QKeyedTable::new(QTable::new(key_columns, key_values)?, table)
+  }
+
+  /// Interpret a dictionary as a keyed table. Fails unless both the keys
+  ///  and the values of the dictionary are tables.
+  pub fn from_dictionary(dictionary: QDictionary) -> io::Result<Self> {
+    match dictionary.into_parts() {
+      (Q::Table(keys), Q::Table(values)) => QKeyedTable::new(keys, values),
+      _ => Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "the dictionary does not map a table to a table",
+      )),
+    }
+  }
+
+  /// Rebuild the dictionary-of-tables representation, e.g. to serialize
+  ///  the keyed table.
+  pub fn into_dictionary(self) -> QDictionary {
+    QDictionary::new(Q::Table(self.keys), Q::Table(self.values))
+  }
+
+  /// Key table holding the key columns.
+  pub fn keys(&self) -> &QTable {
+    &self.keys
+  }
+
+  /// Value table holding the value columns.
+  pub fn values(&self) -> &QTable {
+    &self.values
+  }
+
+  /// Decompose the keyed table into its key table and value table.
+  pub fn into_parts(self) -> (QTable, QTable) {
+    (self.keys, self.values)
+  }
+
+  /// Number of rows of the keyed table.
+  pub fn row_count(&self) -> usize {
+    self.keys.row_count()
+  }
+
+  /// Value row stored under the given key, like indexing a keyed table
+  ///  with a key record in q, or `None` when the key is absent. Looks the
+  ///  key up by a linear scan; use [`QTable::lj`] to resolve many keys at
+  ///  once.
+  /// # Parameters
+  /// - `key`: Key atoms, one per key column and in key-column order, e.g.
+  ///   `&[Q::Symbol("a".to_string())]` for a table keyed on one symbol
+  ///   column.
+  pub fn get_row(&self, key: &[Q]) -> io::Result<Option<Row<'_>>> {
+    Ok(self.key_position(key)?.map(|index| Row {
+      table: &self.values,
+      index,
+    }))
+  }
+
+  /// `true` if a value row is stored under the given key.
+  /// # Parameters
+  /// - `key`: Key atoms, one per key column and in key-column order.
+  pub fn contains_key(&self, key: &[Q]) -> io::Result<bool> {
+    Ok(self.key_position(key)?.is_some())
+  }
+
+  /// Index of the first row whose key columns hold the given atoms,
+  ///  failing when the number of atoms does not match the number of key
+  ///  columns.
+  fn key_position(&self, key: &[Q]) -> io::Result<Option<usize>> {
+    let columns = self.keys.values();
+    if key.len() != columns.len() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "the key has {} value(s) but the keyed table has {} key column(s)",
+          key.len(),
+          columns.len()
+        ),
+      ));
+    }
+    Ok((0..self.row_count()).find(|&row| {
+      columns
+        .iter()
+        .zip(key)
+        .all(|(column, atom)| column.get(row).as_ref() == Some(atom))
+    }))
+  }
+
+  /// Remove the key, like `0!` in q: the key columns and value columns
+  ///  are glued back into one plain table.
+  pub fn unkey(self) -> QTable {
+    let (mut columns, mut values) = self.keys.into_parts();
+    let (value_columns, value_values) = self.values.into_parts();
+    columns.extend(value_columns);
+    values.extend(value_values);
+    QTable { columns, values }
+  }
+}
+
+//%% QSchema %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Element type of a table column, named after the q datatype.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QType {
+  /// Bool column (`1h`).
+  Bool,
+  /// GUID column (`2h`).
+  Guid,
+  /// Byte column (`4h`).
+  Byte,
+  /// Short column (`5h`).
+  Short,
+  /// Int column (`6h`).
+  Int,
+  /// Long column (`7h`).
+  Long,
+  /// Real column (`8h`).
+  Real,
+  /// Float column (`9h`).
+  Float,
+  /// Char column (`10h`), i.e. one string per table.
+  Char,
+  /// Symbol column (`11h`).
+  Symbol,
+  /// Timestamp column (`12h`).
+  Timestamp,
+  /// Month column (`13h`).
+  Month,
+  /// Date column (`14h`).
+  Date,
+  /// Datetime column (`15h`).
+  Datetime,
+  /// Timespan column (`16h`).
+  Timespan,
+  /// Minute column (`17h`).
+  Minute,
+  /// Second column (`18h`).
+  Second,
+  /// Time column (`19h`).
+  Time,
+  /// Enumerated symbol column (`20h`-`76h`).
+  Enum,
+  /// Mixed column (`0h`), e.g. one string per row.
+  Mixed,
+}
+
+impl QType {
+  /// Element type of a column with the given q list type code.
+  pub fn from_type_code(type_code: i8) -> Option<Self> {
+    Some(match type_code.unsigned_abs() as i8 {
+      1 => QType::Bool,
+      2 => QType::Guid,
+      4 => QType::Byte,
+      5 => QType::Short,
+      6 => QType::Int,
+      7 => QType::Long,
+      8 => QType::Real,
+      9 => QType::Float,
+      10 => QType::Char,
+      11 => QType::Symbol,
+      12 => QType::Timestamp,
+      13 => QType::Month,
+      14 => QType::Date,
+      15 => QType::Datetime,
+      16 => QType::Timespan,
+      17 => QType::Minute,
+      18 => QType::Second,
+      19 => QType::Time,
+      Q_ENUM_MIN..=Q_ENUM_MAX => QType::Enum,
+      0 => QType::Mixed,
+      _ => return None,
+    })
+  }
+
+  /// q name of the type, for error messages.
+  pub fn name(self) -> &'static str {
+    match self {
+      QType::Bool => "bool",
+      QType::Guid => "guid",
+      QType::Byte => "byte",
+      QType::Short => "short",
+      QType::Int => "int",
+      QType::Long => "long",
+      QType::Real => "real",
+      QType::Float => "float",
+      QType::Char => "char",
+      QType::Symbol => "symbol",
+      QType::Timestamp => "timestamp",
+      QType::Month => "month",
+      QType::Date => "date",
+      QType::Datetime => "datetime",
+      QType::Timespan => "timespan",
+      QType::Minute => "minute",
+      QType::Second => "second",
+      QType::Time => "time",
+      QType::Enum => "enum",
+      QType::Mixed => "mixed",
+    }
+  }
+}
+
+/// Schema of a table: the column names and element types, in order. Built
+///  by hand for an expected layout or extracted from a table with
+///  [`QTable::schema`], and enforced with [`validate`](QSchema::validate).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QSchema {
+  /// Column names and element types, in order.
+  columns: Vec<(String, QType)>,
+}
+
+impl QSchema {
+  /// Construct a schema from column names and element types.
+  pub fn new(columns: Vec<(String, QType)>) -> Self {
+    QSchema { columns }
+  }
+
+  /// Column names and element types, in order.
+  pub fn columns(&self) -> &[(String, QType)] {
+    &self.columns
+  }
+
+  /// Check a table against the schema: the same column names in the same
+  ///  order, each holding the expected element type.
+  /// # Parameters
+  /// - `table`: Table to check.
+  pub fn validate(&self, table: &QTable) -> io::Result<()> {
+    let columns = table.columns();
+    if columns.len() != self.columns.len() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+          "the table has {} column(s) ({}) but {} were expected",
+          columns.len(),
+          columns.join(", "),
+          self.columns.len()
+        ),
+      ));
+    }
+    for (index, (name, expected)) in self.columns.iter().enumerate() {
+      if &columns[index] != name {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidData,
+          format!(
+            "column {} is named '{}' but '{}' was expected",
+            index, columns[index], name
+          ),
+        ));
+      }
+      let value = &table.values()[index];
+      let actual = QType::from_type_code(value.q_type());
+      if actual != Some(*expected) {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidData,
+          format!(
+            "column '{}' holds q {} values but {} was expected",
+            name,
+            actual.map_or("non-list", QType::name),
+            expected.name()
+          ),
+        ));
+      }
+    }
+    Ok(())
+  }
+}
+
+//%% QDictionary %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// q dictionary mapping a key list to a value list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QDictionary {
+  /// Key object. Always a list.
+  keys: Box<Q>,
+  /// Value object. Always a list of the same length as `keys`.
+  values: Box<Q>,
+  /// `true` if the keys carry the `` `s#`` attribute (type 127h on the
+  ///  wire instead of 99h).
+  sorted: bool,
+}
+
+impl QDictionary {
+  /// Construct a dictionary from a key object and a value object.
+  pub fn new(keys: Q, values: Q) -> Self {
+    QDictionary {
+      keys: Box::new(keys),
+      values: Box::new(values),
+      sorted: false,
+    }
+  }
+
+  /// Construct a sorted dictionary, i.e. one whose keys carry the `` `s#``
+  ///  attribute, as q builds for a keyed table sorted by its key column.
+  pub fn sorted(keys: Q, values: Q) -> Self {
+    QDictionary {
+      keys: Box::new(keys),
+      values: Box::new(values),
+      sorted: true,
+    }
+  }
+
+  /// `true` if the keys carry the `` `s#`` attribute.
+  pub fn is_sorted(&self) -> bool {
+    self.sorted
+  }
+
+  /// Key object of the dictionary.
+  pub fn keys(&self) -> &Q {
+    &self.keys
+  }
+
+  /// Value object of the dictionary.
+  pub fn values(&self) -> &Q {
+    &self.values
+  }
+
+  /// Decompose the dictionary into its key object and value object.
+  pub fn into_parts(self) -> (Q, Q) {
+    (*self.keys, *self.values)
+  }
+
+  /// Build a dictionary from key-value pairs, e.g. collected from a Rust
+  ///  map: the keys become one list and the values another, each of the
+  ///  q type the element type converts into.
+  /// # Example
+  /// ```
+  /// use rustkdb::qtype::QDictionary;
+  ///
+  /// let limits = QDictionary::from_pairs(vec![("fast", 100_i64), ("slow", 10)]);
+  /// ```
+  pub fn from_pairs<K, V, I>(pairs: I) -> Self
+  where
+    K: IntoQ,
+    V: IntoQ,
+    I: IntoIterator<Item = (K, V)>,
+  {
+    let (keys, values): (Vec<K>, Vec<V>) = pairs.into_iter().unzip();
+    QDictionary::new(keys.into_q(), values.into_q())
+  }
+
+  /// Value mapped to the given key, like `dictionary[key]` in q, or
+  ///  `None` when the key is absent. Looks the key up by a linear scan;
+  ///  convert with [`into_hashmap`](QDictionary::into_hashmap) for
+  ///  repeated lookups.
+  /// # Parameters
+  /// - `key`: Key atom to look up, e.g. `Q::Symbol` for a symbol-keyed
+  ///   dictionary.
+  pub fn get(&self, key: &Q) -> Option<Q> {
+    (0..self.keys.len())
+      .find(|&index| self.keys.get(index).as_ref() == Some(key))
+      .and_then(|index| self.values.get(index))
+  }
+
+  /// Convert into a `HashMap` with typed keys and values. The first
+  ///  occurrence of a duplicated key wins, as in a q lookup.
+  pub fn into_hashmap<K, V>(self) -> io::Result<HashMap<K, V>>
+  where
+    K: FromQ + Eq + std::hash::Hash,
+    V: FromQ,
+  {
+    let (keys, values) = self.into_parts();
+    let keys = K::vec_from_q(keys)?;
+    let values = V::vec_from_q(values)?;
+    let mut map = HashMap::with_capacity(keys.len());
+    for (key, value) in keys.into_iter().zip(values) {
+      map.entry(key).or_insert(value);
+    }
+    Ok(map)
+  }
+
+  /// Convert into a `BTreeMap` with typed keys and values. The first
+  ///  occurrence of a duplicated key wins, as in a q lookup.
+  pub fn into_btreemap<K, V>(self) -> io::Result<BTreeMap<K, V>>
+  where
+    K: FromQ + Ord,
+    V: FromQ,
+  {
+    let (keys, values) = self.into_parts();
+    let keys = K::vec_from_q(keys)?;
+    let values = V::vec_from_q(values)?;
+    let mut map = BTreeMap::new();
+    for (key, value) in keys.into_iter().zip(values) {
+      map.entry(key).or_insert(value);
+    }
+    Ok(map)
+  }
+}
+
+//%% QEnum %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Enumerated symbol list (types 20h-76h), e.g. a `` `sym$`` column read
+///  from a splayed or partitioned table. Only the indices into the enum
+///  domain travel over IPC; the domain itself has to be fetched separately
+///  and applied with [`resolve`](QEnum::resolve).
+#[derive(Clone, Debug, PartialEq)]
+pub struct QEnum {
+  /// q type code of the enumeration, 20 for the `sym` domain.
+  type_code: i8,
+  /// Indices into the enum domain.
+  indices: QList<i64>,
+}
+
+impl QEnum {
+  /// Construct an enumerated symbol list.
+  /// # Parameters
+  /// - `type_code`: q type code of the enumeration, between 20 and 76.
+  /// - `indices`: Indices into the enum domain.
+  pub fn new(type_code: i8, indices: QList<i64>) -> io::Result<Self> {
+    if !(Q_ENUM_MIN..=Q_ENUM_MAX).contains(&type_code) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("not an enum type code: {}", type_code),
+      ));
+    }
+    Ok(QEnum { type_code, indices })
+  }
+
+  /// q type code of the enumeration.
+  pub fn type_code(&self) -> i8 {
+    self.type_code
+  }
+
+  /// Indices into the enum domain.
+  pub fn indices(&self) -> &QList<i64> {
+    &self.indices
+  }
+
+  /// Resolve the indices against the domain values, yielding the symbol
+  ///  list the enumeration stands for. The list attribute is preserved.
+  /// # Parameters
+  /// - `domain`: Values of the enum domain, e.g. the `sym` list.
+  pub fn resolve(&self, domain: &[String]) -> io::Result<Q> {
+    let mut symbols = Vec::with_capacity(self.indices.len());
+    for index in self.indices.data() {
+      let symbol = usize::try_from(*index)
+        .ok()
+        .and_then(|position| domain.get(position))
+        .ok_or_else(|| {
+          io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("enum index {} is outside the domain", index),
+          )
+        })?;
+      symbols.push(symbol.clone());
+    }
+    Ok(Q::SymbolList(QList::with_attribute(
+      symbols,
+      self.indices.attribute(),
+    )))
+  }
+}
+
+//%% QFunction %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// q function object, preserved in its wire form. Functions cannot be
+///  applied in Rust, but they can be inspected and sent back to a q
+///  process unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QFunction {
+  /// Lambda (type 100h).
+  Lambda {
+    /// Namespace context the lambda was defined in, `""` for the default
+    ///  namespace.
+    context: String,
+    /// Definition text, e.g. `"{x+y}"`.
+    body: String,
+  },
+  /// Unary primitive (type 101h) by primitive code, e.g. `flip` is 41.
+  UnaryPrimitive(u8),
+  /// Operator (type 102h) by operator code, e.g. `+` is 1.
+  Operator(u8),
+  /// Iterator (type 103h) by iterator code, e.g. `'` is 0.
+  Iterator(u8),
+  /// Projection (type 104h): the projected function followed by the fixed
+  ///  arguments, with [`Q::Null`] in the unfixed positions.
+  Projection(Vec<Q>),
+  /// Composition (type 105h): the composed functions.
+  Composition(Vec<Q>),
+  /// Function derived by applying an iterator to another function
+  ///  (types 106h-111h): the type code and the modified function.
+  Derived(i8, Box<Q>),
+}
+
+impl QFunction {
+  /// Construct a lambda in the default namespace, e.g. for `set`ting a
+  ///  function on a remote process or heading a functional query.
+  /// # Parameters
+  /// - `body`: Definition text, e.g. `"{x+y}"`.
+  pub fn lambda(body: &str) -> Self {
+    QFunction::Lambda {
+      context: String::new(),
+      body: body.to_string(),
+    }
+  }
+
+  /// Construct a lambda defined in the given namespace context.
+  /// # Parameters
+  /// - `context`: Namespace the lambda evaluates in, e.g. `"u"` for `.u`.
+  /// - `body`: Definition text.
+  pub fn lambda_in(context: &str, body: &str) -> Self {
+    QFunction::Lambda {
+      context: context.to_string(),
+      body: body.to_string(),
+    }
+  }
+
+  /// Construct a projection of a function onto fixed arguments; a
+  ///  [`Q::Null`] argument leaves that position open.
+  /// # Parameters
+  /// - `function`: Projected function.
+  /// - `arguments`: Fixed arguments in order.
+  pub fn projection(function: Q, arguments: Vec<Q>) -> Self {
+    let mut items = Vec::with_capacity(1 + arguments.len());
+    items.push(function);
+    items.extend(arguments);
+    QFunction::Projection(items)
+  }
+}
+
+//%% Q %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Rust representation of a q object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Q {
+  /// Bool atom, e.g. `1b`.
+  Bool(bool),
+  /// GUID atom stored as 16 raw bytes.
+  Guid([u8; 16]),
+  /// Byte atom, e.g. `0x2a`.
+  Byte(u8),
+  /// Short atom, e.g. `42h`.
+  Short(i16),
+  /// Int atom, e.g. `42i`.
+  Int(i32),
+  /// Long atom, e.g. `42`.
+  Long(i64),
+  /// Real atom, e.g. `4.2e`.
+  Real(f32),
+  /// Float atom, e.g. `4.2`.
+  Float(f64),
+  /// Char atom, e.g. `"a"`.
+  Char(char),
+  /// Symbol atom, e.g. `` `abc``.
+  Symbol(String),
+  /// Timestamp atom as nanoseconds since 2000.01.01D00:00:00.
+  Timestamp(i64),
+  /// Month atom as months since 2000.01m.
+  Month(i32),
+  /// Date atom as days since 2000.01.01.
+  Date(i32),
+  /// Datetime atom as fractional days since 2000.01.01T00:00:00.
+  Datetime(f64),
+  /// Timespan atom as nanoseconds.
+  Timespan(i64),
+  /// Minute atom as minutes since midnight.
+  Minute(i32),
+  /// Second atom as seconds since midnight.
+  Second(i32),
+  /// Time atom as milliseconds since midnight.
+  Time(i32),
+  /// Bool list, e.g. `101b`.
+  BoolList(QList<bool>),
+  /// GUID list.
+  GuidList(QList<[u8; 16]>),
+  /// Byte list, e.g. `0x2a2b`.
+  ByteList(QList<u8>),
+  /// Short list, e.g. `41 42h`.
+  ShortList(QList<i16>),
+  /// Int list, e.g. `41 42i`.
+  IntList(QList<i32>),
+  /// Long list, e.g. `41 42`.
+  LongList(QList<i64>),
+  /// Real list, e.g. `4.1 4.2e`.
+  RealList(QList<f32>),
+  /// Float list, e.g. `4.1 4.2`.
+  FloatList(QList<f64>),
+  /// Char list (string), e.g. `"abc"`.
+  String(String),
+  /// Symbol list, e.g. `` `abc`def``.
+  SymbolList(QList<String>),
+  /// Timestamp list.
+  TimestampList(QList<i64>),
+  /// Month list.
+  MonthList(QList<i32>),
+  /// Date list.
+  DateList(QList<i32>),
+  /// Datetime list.
+  DatetimeList(QList<f64>),
+  /// Timespan list.
+  TimespanList(QList<i64>),
+  /// Minute list.
+  MinuteList(QList<i32>),
+  /// Second list.
+  SecondList(QList<i32>),
+  /// Time list.
+  TimeList(QList<i32>),
+  /// Enumerated symbol list, e.g. `` `sym$`a`b``.
+  Enum(QEnum),
+  /// Mixed list, e.g. `(1b; `abc; 4.2)`.
+  MixedList(Vec<Q>),
+  /// Table.
+  Table(QTable),
+  /// Dictionary or keyed table.
+  Dictionary(QDictionary),
+  /// Function object: lambda, operator, projection and the like.
+  Function(QFunction),
+  /// Object of a type this crate does not know how to decode, e.g. a
+  ///  foreign object or a type introduced by a newer kdb+ version, kept as
+  ///  its raw wire bytes. Since the length of an unknown object cannot be
+  ///  determined, the bytes run to the end of the enclosing message; the
+  ///  object therefore survives a decode/encode round trip only when it is
+  ///  the last object of the message.
+  Raw {
+    /// q type code of the object.
+    type_code: i8,
+    /// Wire bytes of the object excluding the type code byte.
+    bytes: Vec<u8>,
+  },
+  /// Error atom (type -128h) carrying the error symbol, e.g. an error a
+  ///  gateway embedded in an otherwise successful compound response. A
+  ///  whole response consisting of one error atom is surfaced as an
+  ///  [`io::Error`](std::io::Error) instead.
+  Error(String),
+  /// General null `(::)`.
+  Null,
+}
+
+impl Q {
+  /// q type code of the object, e.g. -7 for a long atom, 7 for a long
+  ///  list, 98 for a table. Enumerations, functions and raw objects report
+  ///  their own type code; an error atom reports -128.
+  pub fn q_type(&self) -> i8 {
+    match self {
+      Q::Bool(_) => -1,
+      Q::Guid(_) => -2,
+      Q::Byte(_) => -4,
+      Q::Short(_) => -5,
+      Q::Int(_) => -6,
+      Q::Long(_) => -7,
+      Q::Real(_) => -8,
+      Q::Float(_) => -9,
+      Q::Char(_) => -10,
+      Q::Symbol(_) => -11,
+      Q::Timestamp(_) => -12,
+      Q::Month(_) => -13,
+      Q::Date(_) => -14,
+      Q::Datetime(_) => -15,
+      Q::Timespan(_) => -16,
+      Q::Minute(_) => -17,
+      Q::Second(_) => -18,
+      Q::Time(_) => -19,
+      Q::BoolList(_) => 1,
+      Q::GuidList(_) => 2,
+      Q::ByteList(_) => 4,
+      Q::ShortList(_) => 5,
+      Q::IntList(_) => 6,
+      Q::LongList(_) => 7,
+      Q::RealList(_) => 8,
+      Q::FloatList(_) => 9,
+      Q::String(_) => 10,
+      Q::SymbolList(_) => 11,
+      Q::TimestampList(_) => 12,
+      Q::MonthList(_) => 13,
+      Q::DateList(_) => 14,
+      Q::DatetimeList(_) => 15,
+      Q::TimespanList(_) => 16,
+      Q::MinuteList(_) => 17,
+      Q::SecondList(_) => 18,
+      Q::TimeList(_) => 19,
+      Q::Enum(enumeration) => enumeration.type_code(),
+      Q::MixedList(_) => Q_MIXED_LIST,
+      Q::Table(_) => Q_TABLE,
+      Q::Dictionary(dictionary) => {
+        if dictionary.is_sorted() {
+          Q_SORTED_DICTIONARY
+        } else {
+          Q_DICTIONARY
+        }
+      }
+      Q::Function(function) => match function {
+        QFunction::Lambda { .. } => Q_LAMBDA,
+        QFunction::UnaryPrimitive(_) => Q_GENERAL_NULL,
+        QFunction::Operator(_) => Q_OPERATOR,
+        QFunction::Iterator(_) => Q_ITERATOR,
+        QFunction::Projection(_) => Q_PROJECTION,
+        QFunction::Composition(_) => Q_COMPOSITION,
+        QFunction::Derived(type_code, _) => *type_code,
+      },
+      Q::Raw { type_code, .. } => *type_code,
+      Q::Error(_) => Q_ERROR,
+      Q::Null => Q_GENERAL_NULL,
+    }
+  }
+
+  /// `true` for an atom, i.e. an object with a negative type code other
+  ///  than an error atom.
+  pub fn is_atom(&self) -> bool {
+    let type_code = self.q_type();
+    type_code < 0 && type_code != Q_ERROR
+  }
+
+  /// `true` for a list of any kind: simple, enumerated or mixed.
+  pub fn is_list(&self) -> bool {
+    (Q_MIXED_LIST..=Q_ENUM_MAX).contains(&self.q_type())
+  }
+
+  /// `true` for a table. A keyed table is a dictionary, not a table.
+  pub fn is_table(&self) -> bool {
+    matches!(self, Q::Table(_))
+  }
+
+  /// `true` for a dictionary or keyed table, sorted or not.
+  pub fn is_dictionary(&self) -> bool {
+    matches!(self, Q::Dictionary(_))
+  }
+
+  /// `true` for a function object of any kind.
+  pub fn is_function(&self) -> bool {
+    matches!(self, Q::Function(_))
+  }
+
+  /// `true` for an error atom, whether received or constructed.
+  pub fn is_error(&self) -> bool {
+    matches!(self, Q::Error(_))
+  }
+
+  /// Element count of the object following q `count` semantics: the length
+  ///  of a list or string, the number of rows of a table, the number of
+  ///  entries of a dictionary, and 1 for any atom.
+  pub fn len(&self) -> usize {
+    match self {
+      Q::BoolList(list) => list.len(),
+      Q::GuidList(list) => list.len(),
+      Q::ByteList(list) => list.len(),
+      Q::ShortList(list) => list.len(),
+      Q::IntList(list) => list.len(),
+      Q::LongList(list) => list.len(),
+      Q::RealList(list) => list.len(),
+      Q::FloatList(list) => list.len(),
+      Q::String(value) => value.chars().count(),
+      Q::SymbolList(list) => list.len(),
+      Q::TimestampList(list) => list.len(),
+      Q::MonthList(list) => list.len(),
+      Q::DateList(list) => list.len(),
+      Q::DatetimeList(list) => list.len(),
+      Q::TimespanList(list) => list.len(),
+      Q::MinuteList(list) => list.len(),
+      Q::SecondList(list) => list.len(),
+      Q::TimeList(list) => list.len(),
+      Q::Enum(enumeration) => enumeration.indices().len(),
+      Q::MixedList(items) => items.len(),
+      Q::Table(table) => table.values().first().map_or(0, Q::len),
+      Q::Dictionary(dictionary) => dictionary.keys().len(),
+      _ => 1,
+    }
+  }
+
+  /// `true` for a container with no elements. Atoms are never empty.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Element of a list object at the given index, as the matching atom: a
+  ///  long list yields [`Q::Long`], a string yields [`Q::Char`], a mixed
+  ///  list yields a clone of the element. An enumerated list yields its
+  ///  index into the domain as [`Q::Long`], since the symbol itself is not
+  ///  known without the domain. `None` for an atom, a table or a
+  ///  dictionary, and for an index beyond the end of the list.
+  pub fn get(&self, index: usize) -> Option<Q> {
+    match self {
+      Q::BoolList(list) => list.data().get(index).map(|item| Q::Bool(*item)),
+      Q::GuidList(list) => list.data().get(index).map(|item| Q::Guid(*item)),
+      Q::ByteList(list) => list.data().get(index).map(|item| Q::Byte(*item)),
+      Q::ShortList(list) => list.data().get(index).map(|item| Q::Short(*item)),
+      Q::IntList(list) => list.data().get(index).map(|item| Q::Int(*item)),
+      Q::LongList(list) => list.data().get(index).map(|item| Q::Long(*item)),
+      Q::RealList(list) => list.data().get(index).map(|item| Q::Real(*item)),
+      Q::FloatList(list) => list.data().get(index).map(|item| Q::Float(*item)),
+      Q::String(value) => value.chars().nth(index).map(Q::Char),
+      Q::SymbolList(list) => list.data().get(index).map(|item| Q::Symbol(item.clone())),
+      Q::TimestampList(list) => list.data().get(index).map(|item| Q::Timestamp(*item)),
+      Q::MonthList(list) => list.data().get(index).map(|item| Q::Month(*item)),
+      Q::DateList(list) => list.data().get(index).map(|item| Q::Date(*item)),
+      Q::DatetimeList(list) => list.data().get(index).map(|item| Q::Datetime(*item)),
+      Q::TimespanList(list) => list.data().get(index).map(|item| Q::Timespan(*item)),
+      Q::MinuteList(list) => list.data().get(index).map(|item| Q::Minute(*item)),
+      Q::SecondList(list) => list.data().get(index).map(|item| Q::Second(*item)),
+      Q::TimeList(list) => list.data().get(index).map(|item| Q::Time(*item)),
+      Q::Enum(enumeration) => enumeration
+        .indices()
+        .data()
+        .get(index)
+        .map(|item| Q::Long(*item)),
+      Q::MixedList(items) => items.get(index).cloned(),
+      _ => None,
+    }
+  }
+
+  /// Sub-list of a list object over the given index range, as a list of
+  ///  the same type. List attributes are dropped, as they describe the
+  ///  whole list. `None` for an object that is not a list and for a range
+  ///  reaching beyond the end of the list.
+  pub fn slice(&self, range: std::ops::Range<usize>) -> Option<Q> {
+    fn sub<T: Clone>(list: &QList<T>, range: std::ops::Range<usize>) -> Option<QList<T>> {
+      list
+        .data()
+        .get(range)
+        .map(|items| QList::new(items.to_vec()))
+    }
+    match self {
+      Q::BoolList(list) => sub(list, range).map(Q::BoolList),
+      Q::GuidList(list) => sub(list, range).map(Q::GuidList),
+      Q::ByteList(list) => sub(list, range).map(Q::ByteList),
+      Q::ShortList(list) => sub(list, range).map(Q::ShortList),
+      Q::IntList(list) => sub(list, range).map(Q::IntList),
+      Q::LongList(list) => sub(list, range).map(Q::LongList),
+      Q::RealList(list) => sub(list, range).map(Q::RealList),
+      Q::FloatList(list) => sub(list, range).map(Q::FloatList),
+      Q::String(value) => {
+        if range.end > value.chars().count() || range.start > range.end {
+          return None;
+        }
+        Some(Q::String(
+          value
+            .chars()
+            .skip(range.start)
+            .take(range.end - range.start)
+            .collect(),
+        ))
+      }
+      Q::SymbolList(list) => sub(list, range).map(Q::SymbolList),
+      Q::TimestampList(list) => sub(list, range).map(Q::TimestampList),
+      Q::MonthList(list) => sub(list, range).map(Q::MonthList),
+      Q::DateList(list) => sub(list, range).map(Q::DateList),
+      Q::DatetimeList(list) => sub(list, range).map(Q::DatetimeList),
+      Q::TimespanList(list) => sub(list, range).map(Q::TimespanList),
+      Q::MinuteList(list) => sub(list, range).map(Q::MinuteList),
+      Q::SecondList(list) => sub(list, range).map(Q::SecondList),
+      Q::TimeList(list) => sub(list, range).map(Q::TimeList),
+      Q::Enum(enumeration) => {
+        let indices = sub(enumeration.indices(), range)?;
+        Some(Q::Enum(
+          QEnum::new(enumeration.type_code(), indices)
+            .expect("type code validated on construction"),
+        ))
+      }
+      Q::MixedList(items) => items.get(range).map(|sub| Q::MixedList(sub.to_vec())),
+      _ => None,
+    }
+  }
+
+  /// Cast the object into another numeric or temporal type, mirroring q's
+  ///  `$` operator: `` `int$ `` narrows a long, `` `date$ `` truncates a
+  ///  timestamp to the day it falls on, `` `time$ `` keeps the time of day.
+  ///  Floats round half to even on the way into an integer type. Typed
+  ///  nulls and infinities map onto the null and infinities of the target
+  ///  type; an ordinary value that does not fit the target fails with an
+  ///  error of kind `InvalidData`. Lists cast element by element into the
+  ///  list of the target type, dropping any attribute.
+  /// # Parameters
+  /// - `type_code`: q type code of the target, negative for an atom (e.g.
+  ///   -6 for int) and positive for a list (e.g. 6 for an int list).
+  /// # Note
+  /// Casts involving months or datetimes and any other temporal type are
+  /// not supported, as they need calendar arithmetic.
+  pub fn cast(&self, type_code: i8) -> io::Result<Q> {
+    if self.q_type() == type_code {
+      return Ok(self.clone());
+    }
+    if type_code > 0 {
+      if !self.is_list() {
+        return Err(unsupported_cast(self, type_code));
+      }
+      let mut atoms = Vec::with_capacity(self.len());
+      for index in 0..self.len() {
+        atoms.push(self.get(index).expect("index within length").cast(-type_code)?);
+      }
+      return Ok(match type_code {
+        5 => Q::ShortList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Short(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        6 => Q::IntList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Int(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        7 => Q::LongList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Long(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        8 => Q::RealList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Real(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        9 => Q::FloatList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Float(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        12 => Q::TimestampList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Timestamp(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        13 => Q::MonthList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Month(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        14 => Q::DateList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Date(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        15 => Q::DatetimeList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Datetime(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        16 => Q::TimespanList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Timespan(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        17 => Q::MinuteList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Minute(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        18 => Q::SecondList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Second(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        19 => Q::TimeList(QList::new(
+          atoms
+            .into_iter()
+            .map(|atom| match atom {
+              Q::Time(value) => value,
+              _ => unreachable!("cast produced the target atom"),
+            })
+            .collect(),
+        )),
+        _ => return Err(unsupported_cast(self, type_code)),
+      });
+    }
+    let source = self.q_type();
+    let mut scalar = match self {
+      Q::Bool(value) => Scalar::Int(*value as i64),
+      Q::Byte(value) => Scalar::Int(*value as i64),
+      Q::Short(value) => lift_short(*value),
+      Q::Int(value)
+      | Q::Month(value)
+      | Q::Date(value)
+      | Q::Minute(value)
+      | Q::Second(value)
+      | Q::Time(value) => lift_int(*value),
+      Q::Long(value) | Q::Timestamp(value) | Q::Timespan(value) => lift_long(*value),
+      Q::Real(value) => lift_float(*value as f64),
+      Q::Float(value) | Q::Datetime(value) => lift_float(*value),
+      _ => return Err(unsupported_cast(self, type_code)),
+    };
+    if CONVERTIBLE_TEMPORALS.contains(&source) && CONVERTIBLE_TEMPORALS.contains(&type_code) {
+      scalar = match scalar {
+        Scalar::Int(value) => Scalar::Int(
+          convert_temporal(value, source, type_code)
+            .ok_or_else(|| unsupported_cast(self, type_code))?,
+        ),
+        symbolic => symbolic,
+      };
+    } else if matches!(source, -13 | -15) && (-19..=-12).contains(&type_code)
+      || matches!(type_code, -13 | -15) && (-19..=-12).contains(&source)
+    {
+      // Months and datetimes cannot be unit-converted into the other
+      //  temporal types.
+      return Err(unsupported_cast(self, type_code));
+    }
+    match type_code {
+      -5 => Ok(Q::Short(
+        int_value(scalar, i16::MIN as i64, i16::MAX as i64, type_code)? as i16,
+      )),
+      -6 => Ok(Q::Int(
+        int_value(scalar, i32::MIN as i64, i32::MAX as i64, type_code)? as i32,
+      )),
+      -7 => Ok(Q::Long(int_value(scalar, i64::MIN, i64::MAX, type_code)?)),
+      -8 => Ok(Q::Real(float_value(scalar) as f32)),
+      -9 => Ok(Q::Float(float_value(scalar))),
+      -12 => Ok(Q::Timestamp(int_value(scalar, i64::MIN, i64::MAX, type_code)?)),
+      -13 => Ok(Q::Month(
+        int_value(scalar, i32::MIN as i64, i32::MAX as i64, type_code)? as i32,
+      )),
+      -14 => Ok(Q::Date(
+        int_value(scalar, i32::MIN as i64, i32::MAX as i64, type_code)? as i32,
+      )),
+      -15 => Ok(Q::Datetime(float_value(scalar))),
+      -16 => Ok(Q::Timespan(int_value(scalar, i64::MIN, i64::MAX, type_code)?)),
+      -17 => Ok(Q::Minute(
+        int_value(scalar, i32::MIN as i64, i32::MAX as i64, type_code)? as i32,
+      )),
+      -18 => Ok(Q::Second(
+        int_value(scalar, i32::MIN as i64, i32::MAX as i64, type_code)? as i32,
+      )),
+      -19 => Ok(Q::Time(
+        int_value(scalar, i32::MIN as i64, i32::MAX as i64, type_code)? as i32,
+      )),
+      _ => Err(unsupported_cast(self, type_code)),
+    }
+  }
+
+  /// Round down to the nearest multiple of `interval`, like `xbar` in q,
+  ///  counted in the native unit of the type — nanoseconds for timestamps
+  ///  and timespans, milliseconds for times, minutes for minutes and so on
+  ///  — e.g. to truncate timestamps to bar boundaries when building bars
+  ///  locally. Applies to the int-backed temporal atoms and lists and to
+  ///  int and long values; nulls and infinities pass through unchanged.
+  /// # Parameters
+  /// - `interval`: Width of a bar in the native unit. Must be positive.
+  pub fn xbar(&self, interval: i64) -> io::Result<Q> {
+    if interval <= 0 {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "xbar interval must be positive",
+      ));
+    }
+    let narrow = || {
+      i32::try_from(interval).map_err(|_| {
+        io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "xbar interval does not fit the value type",
+        )
+      })
+    };
+    Ok(match self {
+      Q::Long(value) => Q::Long(xbar_i64(*value, interval)),
+      Q::Timestamp(value) => Q::Timestamp(xbar_i64(*value, interval)),
+      Q::Timespan(value) => Q::Timespan(xbar_i64(*value, interval)),
+      Q::Int(value) => Q::Int(xbar_i32(*value, narrow()?)),
+      Q::Month(value) => Q::Month(xbar_i32(*value, narrow()?)),
+      Q::Date(value) => Q::Date(xbar_i32(*value, narrow()?)),
+      Q::Minute(value) => Q::Minute(xbar_i32(*value, narrow()?)),
+      Q::Second(value) => Q::Second(xbar_i32(*value, narrow()?)),
+      Q::Time(value) => Q::Time(xbar_i32(*value, narrow()?)),
+      Q::LongList(list) => Q::LongList(xbar_i64_list(list, interval)),
+      Q::TimestampList(list) => Q::TimestampList(xbar_i64_list(list, interval)),
+      Q::TimespanList(list) => Q::TimespanList(xbar_i64_list(list, interval)),
+      Q::IntList(list) => Q::IntList(xbar_i32_list(list, narrow()?)),
+      Q::MonthList(list) => Q::MonthList(xbar_i32_list(list, narrow()?)),
+      Q::DateList(list) => Q::DateList(xbar_i32_list(list, narrow()?)),
+      Q::MinuteList(list) => Q::MinuteList(xbar_i32_list(list, narrow()?)),
+      Q::SecondList(list) => Q::SecondList(xbar_i32_list(list, narrow()?)),
+      Q::TimeList(list) => Q::TimeList(xbar_i32_list(list, narrow()?)),
+      other => {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidData,
+          format!(
+            "cannot apply xbar to a q {}",
+            crate::convert::q_type_name(other)
+          ),
+        ))
+      }
+    })
+  }
+
+  /// Iterate over the elements of a list object, yielding each element as
+  ///  the matching atom (see [`get`](Q::get)). Empty for an object that is
+  ///  not a list, so atoms can be passed through the same code path.
+  pub fn iter(&self) -> QIter<'_> {
+    QIter {
+      object: self,
+      index: 0,
+      remaining: if self.is_list() { self.len() } else { 0 },
+    }
+  }
+
+  /// `true` for the typed null of an atom type (`0Nh`, `0n`, `` ` `` and
+  ///  the like) as well as for the general null `(::)`.
+  pub fn is_null(&self) -> bool {
+    match self {
+      Q::Guid(value) => *value == [0; 16],
+      Q::Short(value) => *value == i16::MIN,
+      Q::Int(value) => *value == i32::MIN,
+      Q::Long(value) => *value == i64::MIN,
+      Q::Real(value) => value.is_nan(),
+      Q::Float(value) => value.is_nan(),
+      Q::Char(value) => *value == ' ',
+      Q::Symbol(value) => value.is_empty(),
+      Q::Timestamp(value) => *value == i64::MIN,
+      Q::Month(value) => *value == i32::MIN,
+      Q::Date(value) => *value == i32::MIN,
+      Q::Datetime(value) => value.is_nan(),
+      Q::Timespan(value) => *value == i64::MIN,
+      Q::Minute(value) => *value == i32::MIN,
+      Q::Second(value) => *value == i32::MIN,
+      Q::Time(value) => *value == i32::MIN,
+      Q::Null => true,
+      _ => false,
+    }
+  }
+
+  /// `true` for the typed null of an atom type as well as for the general
+  ///  null `(::)`. Alias of [`is_null`](Q::is_null).
+  pub fn is_null_atom(&self) -> bool {
+    self.is_null()
+  }
+
+  /// `true` for the positive infinity of a numeric or temporal atom type,
+  ///  e.g. `0W`, `0Wi` or `0Wp`.
+  pub fn is_pos_inf(&self) -> bool {
+    match self {
+      Q::Short(value) => *value == i16::MAX,
+      Q::Int(value) => *value == i32::MAX,
+      Q::Long(value) => *value == i64::MAX,
+      Q::Real(value) => *value == f32::INFINITY,
+      Q::Float(value) => *value == f64::INFINITY,
+      Q::Timestamp(value) => *value == i64::MAX,
+      Q::Month(value) => *value == i32::MAX,
+      Q::Date(value) => *value == i32::MAX,
+      Q::Datetime(value) => *value == f64::INFINITY,
+      Q::Timespan(value) => *value == i64::MAX,
+      Q::Minute(value) => *value == i32::MAX,
+      Q::Second(value) => *value == i32::MAX,
+      Q::Time(value) => *value == i32::MAX,
+      _ => false,
+    }
+  }
+
+  /// `true` for the negative infinity of a numeric or temporal atom type,
+  ///  e.g. `-0W`, `-0Wi` or `-0Wp`.
+  pub fn is_neg_inf(&self) -> bool {
+    match self {
+      Q::Short(value) => *value == i16::MIN + 1,
+      Q::Int(value) => *value == i32::MIN + 1,
+      Q::Long(value) => *value == i64::MIN + 1,
+      Q::Real(value) => *value == f32::NEG_INFINITY,
+      Q::Float(value) => *value == f64::NEG_INFINITY,
+      Q::Timestamp(value) => *value == i64::MIN + 1,
+      Q::Month(value) => *value == i32::MIN + 1,
+      Q::Date(value) => *value == i32::MIN + 1,
+      Q::Datetime(value) => *value == f64::NEG_INFINITY,
+      Q::Timespan(value) => *value == i64::MIN + 1,
+      Q::Minute(value) => *value == i32::MIN + 1,
+      Q::Second(value) => *value == i32::MIN + 1,
+      Q::Time(value) => *value == i32::MIN + 1,
+      _ => false,
+    }
+  }
+}
+
+//%% QIter %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Iterator over the elements of a list [`Q`] object, yielding each
+///  element as the matching atom via [`Q::get`]. Built by [`Q::iter`].
+pub struct QIter<'a> {
+  /// Object being iterated.
+  object: &'a Q,
+  /// Next element index.
+  index: usize,
+  /// Element count of the object, 0 for a non-list.
+  remaining: usize,
+}
+
+impl Iterator for QIter<'_> {
+  type Item = Q;
+
+  fn next(&mut self) -> Option<Q> {
+    if self.remaining == 0 {
+      return None;
+    }
+    let item = self.object.get(self.index);
+    self.index += 1;
+    self.remaining -= 1;
+    item
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.remaining, Some(self.remaining))
+  }
+}
+
+impl ExactSizeIterator for QIter<'_> {}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Compare two atoms of the same q type for sorting. Floats compare NaN
+///  (the q null) first, like q `asc`. `None` for a pair that cannot be
+///  ordered.
+fn compare_atoms(a: &Q, b: &Q) -> Option<std::cmp::Ordering> {
+  /// NaN-first total order over floats.
+  fn cmp_float(a: f64, b: f64) -> std::cmp::Ordering {
+    match (a.is_nan(), b.is_nan()) {
+      (true, true) => std::cmp::Ordering::Equal,
+      (true, false) => std::cmp::Ordering::Less,
+      (false, true) => std::cmp::Ordering::Greater,
+      (false, false) => a.partial_cmp(&b).expect("neither value is NaN"),
+    }
+  }
+  match (a, b) {
+    (Q::Bool(a), Q::Bool(b)) => Some(a.cmp(b)),
+    (Q::Guid(a), Q::Guid(b)) => Some(a.cmp(b)),
+    (Q::Byte(a), Q::Byte(b)) => Some(a.cmp(b)),
+    (Q::Short(a), Q::Short(b)) => Some(a.cmp(b)),
+    (Q::Int(a), Q::Int(b)) => Some(a.cmp(b)),
+    (Q::Long(a), Q::Long(b)) => Some(a.cmp(b)),
+    (Q::Timestamp(a), Q::Timestamp(b)) => Some(a.cmp(b)),
+    (Q::Month(a), Q::Month(b)) => Some(a.cmp(b)),
+    (Q::Date(a), Q::Date(b)) => Some(a.cmp(b)),
+    (Q::Timespan(a), Q::Timespan(b)) => Some(a.cmp(b)),
+    (Q::Minute(a), Q::Minute(b)) => Some(a.cmp(b)),
+    (Q::Second(a), Q::Second(b)) => Some(a.cmp(b)),
+    (Q::Time(a), Q::Time(b)) => Some(a.cmp(b)),
+    (Q::Real(a), Q::Real(b)) => Some(cmp_float(*a as f64, *b as f64)),
+    (Q::Float(a), Q::Float(b)) => Some(cmp_float(*a, *b)),
+    (Q::Datetime(a), Q::Datetime(b)) => Some(cmp_float(*a, *b)),
+    (Q::Char(a), Q::Char(b)) => Some(a.cmp(b)),
+    (Q::Symbol(a), Q::Symbol(b)) => Some(a.cmp(b)),
+    _ => None,
+  }
+}
+
+/// Clone the elements of a list in the given row order, attaching the
+///  given attribute to the result.
+fn reorder<T: Clone>(list: &QList<T>, order: &[usize], attribute: Attribute) -> QList<T> {
+  let data = order.iter().map(|&index| list.data()[index].clone()).collect();
+  QList::with_attribute(data, attribute)
+}
+
+/// Attribute carried by a column list, `None` for a non-list column.
+fn column_attribute(column: &Q) -> Attribute {
+  match column {
+    Q::BoolList(list) => list.attribute(),
+    Q::GuidList(list) => list.attribute(),
+    Q::ByteList(list) => list.attribute(),
+    Q::ShortList(list) => list.attribute(),
+    Q::IntList(list) => list.attribute(),
+    Q::LongList(list) => list.attribute(),
+    Q::RealList(list) => list.attribute(),
+    Q::FloatList(list) => list.attribute(),
+    Q::SymbolList(list) => list.attribute(),
+    Q::TimestampList(list) => list.attribute(),
+    Q::MonthList(list) => list.attribute(),
+    Q::DateList(list) => list.attribute(),
+    Q::DatetimeList(list) => list.attribute(),
+    Q::TimespanList(list) => list.attribute(),
+    Q::MinuteList(list) => list.attribute(),
+    Q::SecondList(list) => list.attribute(),
+    Q::TimeList(list) => list.attribute(),
+    Q::Enum(enumeration) => enumeration.indices().attribute(),
+    _ => Attribute::None,
+  }
+}
+
+/// Rebuild a column with its rows in the given order, attaching the given
+///  attribute.
+fn apply_order(column: &Q, order: &[usize], attribute: Attribute) -> io::Result<Q> {
+  match column {
+    Q::BoolList(list) => Ok(Q::BoolList(reorder(list, order, attribute))),
+    Q::GuidList(list) => Ok(Q::GuidList(reorder(list, order, attribute))),
+    Q::ByteList(list) => Ok(Q::ByteList(reorder(list, order, attribute))),
+    Q::ShortList(list) => Ok(Q::ShortList(reorder(list, order, attribute))),
+    Q::IntList(list) => Ok(Q::IntList(reorder(list, order, attribute))),
+    Q::LongList(list) => Ok(Q::LongList(reorder(list, order, attribute))),
+    Q::RealList(list) => Ok(Q::RealList(reorder(list, order, attribute))),
+    Q::FloatList(list) => Ok(Q::FloatList(reorder(list, order, attribute))),
+    Q::SymbolList(list) => Ok(Q::SymbolList(reorder(list, order, attribute))),
+    Q::TimestampList(list) => Ok(Q::TimestampList(reorder(list, order, attribute))),
+    Q::MonthList(list) => Ok(Q::MonthList(reorder(list, order, attribute))),
+    Q::DateList(list) => Ok(Q::DateList(reorder(list, order, attribute))),
+    Q::DatetimeList(list) => Ok(Q::DatetimeList(reorder(list, order, attribute))),
+    Q::TimespanList(list) => Ok(Q::TimespanList(reorder(list, order, attribute))),
+    Q::MinuteList(list) => Ok(Q::MinuteList(reorder(list, order, attribute))),
+    Q::SecondList(list) => Ok(Q::SecondList(reorder(list, order, attribute))),
+    Q::TimeList(list) => Ok(Q::TimeList(reorder(list, order, attribute))),
+    Q::String(value) => {
+      let characters: Vec<char> = value.chars().collect();
+      Ok(Q::String(order.iter().map(|&index| characters[index]).collect()))
+    }
+    Q::Enum(enumeration) => Ok(Q::Enum(
+      QEnum::new(
+        enumeration.type_code(),
+        reorder(enumeration.indices(), order, attribute),
+      )
+      .expect("type code validated on construction"),
+    )),
+    Q::MixedList(items) => Ok(Q::MixedList(
+      order.iter().map(|&index| items[index].clone()).collect(),
+    )),
+    other => Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!(
+        "table column is a q {}, not a list",
+        crate::convert::q_type_name(other)
+      ),
+    )),
+  }
+}
+
+/// Append one atom onto a column list of the matching type. Attributes of
+///  the column are cleared, as the appended value may not respect them.
+fn push_column_atom(column: &mut Q, atom: Q) -> io::Result<()> {
+  match (column, atom) {
+    (Q::BoolList(list), Q::Bool(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::GuidList(list), Q::Guid(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::ByteList(list), Q::Byte(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::ShortList(list), Q::Short(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::IntList(list), Q::Int(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::LongList(list), Q::Long(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::RealList(list), Q::Real(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::FloatList(list), Q::Float(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::SymbolList(list), Q::Symbol(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::TimestampList(list), Q::Timestamp(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::MonthList(list), Q::Month(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::DateList(list), Q::Date(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::DatetimeList(list), Q::Datetime(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::TimespanList(list), Q::Timespan(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::MinuteList(list), Q::Minute(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::SecondList(list), Q::Second(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::TimeList(list), Q::Time(value)) => {
+      list.push(value);
+      list.set_attribute(Attribute::None);
+    }
+    (Q::String(string), Q::Char(value)) => string.push(value),
+    (Q::MixedList(items), value) => items.push(value),
+    (column, atom) => {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "cannot append a q {} to a q {} column",
+          crate::convert::q_type_name(&atom),
+          crate::convert::q_type_name(column)
+        ),
+      ))
+    }
+  }
+  Ok(())
+}
+
+/// Append a column list of the same type onto a column list. Attributes of
+///  the column are cleared, as the appended data may not respect them.
+fn concat_column(column: &mut Q, addition: Q) -> io::Result<()> {
+  match (column, addition) {
+    (Q::BoolList(list), Q::BoolList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::GuidList(list), Q::GuidList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::ByteList(list), Q::ByteList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::ShortList(list), Q::ShortList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::IntList(list), Q::IntList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::LongList(list), Q::LongList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::RealList(list), Q::RealList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::FloatList(list), Q::FloatList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::SymbolList(list), Q::SymbolList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::TimestampList(list), Q::TimestampList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::MonthList(list), Q::MonthList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::DateList(list), Q::DateList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::DatetimeList(list), Q::DatetimeList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::TimespanList(list), Q::TimespanList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::MinuteList(list), Q::MinuteList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::SecondList(list), Q::SecondList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::TimeList(list), Q::TimeList(addition)) => {
+      list.data_mut().extend(addition.into_data());
+      list.set_attribute(Attribute::None);
+    }
+    (Q::String(string), Q::String(addition)) => string.push_str(&addition),
+    (Q::MixedList(items), Q::MixedList(addition)) => items.extend(addition),
+    (column, addition) => {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "cannot concatenate a q {} column onto a q {} column",
+          crate::convert::q_type_name(&addition),
+          crate::convert::q_type_name(column)
+        ),
+      ))
+    }
+  }
+  Ok(())
+}
+
+/// Build the error returned when a column holds another type than the one
+///  a typed accessor expects.
+fn column_type_mismatch(name: &str, column: &Q, expected: &str) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::InvalidData,
+    format!(
+      "column '{}' is a q {}, not a {} list",
+      name,
+      crate::convert::q_type_name(column),
+      expected
+    ),
+  )
+}
+
+/// Overwrite or append the columns of `source` onto a joined table, one
+///  element per entry of `matches`: a matched row takes the source value,
+///  an unmatched row keeps its existing value or holds a typed null in a
+///  column new to the result. Columns named in `skip` (the join keys) are
+///  left untouched.
+fn enrich_columns(
+  result: &mut QTable,
+  source: &QTable,
+  matches: &[Option<usize>],
+  skip: &[String],
+) -> io::Result<()> {
+  for (name, column) in source.columns().iter().zip(source.values()) {
+    if skip.contains(name) {
+      continue;
+    }
+    let existing = result.columns.iter().position(|other| other == name);
+    let mut joined = empty_like(column)?;
+    for (row, matched) in matches.iter().enumerate() {
+      let atom = match matched {
+        Some(source_row) => column.get(*source_row).unwrap_or(Q::Null),
+        None => match existing {
+          Some(position) => result.values[position].get(row).unwrap_or(Q::Null),
+          None => null_like(column),
+        },
+      };
+      push_column_atom(&mut joined, atom)?;
+    }
+    match existing {
+      Some(position) => result.values[position] = joined,
+      None => {
+        result.columns.push(name.clone());
+        result.values.push(joined);
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Append the deterministic byte encoding of an atom to a hash-join key.
+///  The type code tags the payload so values of different types cannot
+///  collide.
+fn push_key_atom(atom: &Q, out: &mut Vec<u8>) -> io::Result<()> {
+  out.push(atom.q_type() as u8);
+  match atom {
+    Q::Bool(value) => out.push(*value as u8),
+    Q::Guid(value) => out.extend_from_slice(value),
+    Q::Byte(value) => out.push(*value),
+    Q::Short(value) => out.extend_from_slice(&value.to_le_bytes()),
+    Q::Int(value)
+    | Q::Month(value)
+    | Q::Date(value)
+    | Q::Minute(value)
+    | Q::Second(value)
+    | Q::Time(value) => out.extend_from_slice(&value.to_le_bytes()),
+    Q::Long(value) | Q::Timestamp(value) | Q::Timespan(value) => {
+      out.extend_from_slice(&value.to_le_bytes())
+    }
+    Q::Real(value) => out.extend_from_slice(&value.to_bits().to_le_bytes()),
+    Q::Float(value) | Q::Datetime(value) => {
+      out.extend_from_slice(&value.to_bits().to_le_bytes())
+    }
+    Q::Char(value) => out.extend_from_slice(&(*value as u32).to_le_bytes()),
+    Q::Symbol(value) => {
+      out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+      out.extend_from_slice(value.as_bytes());
+    }
+    other => {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+          "cannot join on a q {} key",
+          crate::convert::q_type_name(other)
+        ),
+      ))
+    }
+  }
+  Ok(())
+}
+
+/// Empty list of the same q type as the given column, as a seed for
+///  rebuilding the column element by element.
+fn empty_like(column: &Q) -> io::Result<Q> {
+  Ok(match column {
+    Q::BoolList(_) => Q::BoolList(QList::new(Vec::new())),
+    Q::GuidList(_) => Q::GuidList(QList::new(Vec::new())),
+    Q::ByteList(_) => Q::ByteList(QList::new(Vec::new())),
+    Q::ShortList(_) => Q::ShortList(QList::new(Vec::new())),
+    Q::IntList(_) => Q::IntList(QList::new(Vec::new())),
+    Q::LongList(_) => Q::LongList(QList::new(Vec::new())),
+    Q::RealList(_) => Q::RealList(QList::new(Vec::new())),
+    Q::FloatList(_) => Q::FloatList(QList::new(Vec::new())),
+    Q::String(_) => Q::String(String::new()),
+    Q::SymbolList(_) => Q::SymbolList(QList::new(Vec::new())),
+    Q::TimestampList(_) => Q::TimestampList(QList::new(Vec::new())),
+    Q::MonthList(_) => Q::MonthList(QList::new(Vec::new())),
+    Q::DateList(_) => Q::DateList(QList::new(Vec::new())),
+    Q::DatetimeList(_) => Q::DatetimeList(QList::new(Vec::new())),
+    Q::TimespanList(_) => Q::TimespanList(QList::new(Vec::new())),
+    Q::MinuteList(_) => Q::MinuteList(QList::new(Vec::new())),
+    Q::SecondList(_) => Q::SecondList(QList::new(Vec::new())),
+    Q::TimeList(_) => Q::TimeList(QList::new(Vec::new())),
+    Q::MixedList(_) => Q::MixedList(Vec::new()),
+    other => {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+          "table column is a q {}, not a list",
+          crate::convert::q_type_name(other)
+        ),
+      ))
+    }
+  })
+}
+
+/// Typed null atom matching the element type of the given column, to fill
+///  the unmatched rows of a joined column.
+fn null_like(column: &Q) -> Q {
+  match column {
+    Q::BoolList(_) => Q::Bool(false),
+    Q::GuidList(_) => Q::Guid([0; 16]),
+    Q::ByteList(_) => Q::Byte(0),
+    Q::ShortList(_) => Q::Short(i16::MIN),
+    Q::IntList(_) => Q::Int(i32::MIN),
+    Q::LongList(_) => Q::Long(i64::MIN),
+    Q::RealList(_) => Q::Real(f32::NAN),
+    Q::FloatList(_) => Q::Float(f64::NAN),
+    Q::String(_) => Q::Char(' '),
+    Q::SymbolList(_) => Q::Symbol(String::new()),
+    Q::TimestampList(_) => Q::Timestamp(i64::MIN),
+    Q::MonthList(_) => Q::Month(i32::MIN),
+    Q::DateList(_) => Q::Date(i32::MIN),
+    Q::DatetimeList(_) => Q::Datetime(f64::NAN),
+    Q::TimespanList(_) => Q::Timespan(i64::MIN),
+    Q::MinuteList(_) => Q::Minute(i32::MIN),
+    Q::SecondList(_) => Q::Second(i32::MIN),
+    Q::TimeList(_) => Q::Time(i32::MIN),
+    _ => Q::Null,
+  }
+}
+
+/// Round a long-backed value down to the nearest multiple of `interval`,
+///  keeping nulls and infinities as they are.
+fn xbar_i64(value: i64, interval: i64) -> i64 {
+  if value == i64::MIN || value == i64::MIN + 1 || value == i64::MAX {
+    value
+  } else {
+    value - value.rem_euclid(interval)
+  }
+}
+
+/// Round an int-backed value down to the nearest multiple of `interval`,
+///  keeping nulls and infinities as they are.
+fn xbar_i32(value: i32, interval: i32) -> i32 {
+  if value == i32::MIN || value == i32::MIN + 1 || value == i32::MAX {
+    value
+  } else {
+    value - value.rem_euclid(interval)
+  }
+}
+
+/// Apply [`xbar_i64`] element-wise. Rounding down is monotone, so a
+///  sorted list stays sorted; any other attribute no longer holds.
+fn xbar_i64_list(list: &QList<i64>, interval: i64) -> QList<i64> {
+  let data = list.data().iter().map(|value| xbar_i64(*value, interval)).collect();
+  QList::with_attribute(data, bucketed_attribute(list.attribute()))
+}
+
+/// Apply [`xbar_i32`] element-wise. Rounding down is monotone, so a
+///  sorted list stays sorted; any other attribute no longer holds.
+fn xbar_i32_list(list: &QList<i32>, interval: i32) -> QList<i32> {
+  let data = list.data().iter().map(|value| xbar_i32(*value, interval)).collect();
+  QList::with_attribute(data, bucketed_attribute(list.attribute()))
+}
+
+/// Attribute surviving an element-wise rounding: sorted stays, the rest
+///  are dropped.
+fn bucketed_attribute(attribute: Attribute) -> Attribute {
+  if attribute == Attribute::Sorted {
+    Attribute::Sorted
+  } else {
+    Attribute::None
+  }
+}
+
+/// Position of a column by name, failing with an error naming the column.
+fn column_position(columns: &[String], column: &str) -> io::Result<usize> {
+  columns
+    .iter()
+    .position(|name| name == column)
+    .ok_or_else(|| {
+      io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("the table has no column '{}'", column),
+      )
+    })
+}
+
+/// Nanoseconds per day, for conversions between timestamps and the
+///  day-based and time-of-day temporal types.
+const DAY_NANOS: i64 = 86_400_000_000_000;
+
+/// Temporal types a value can be converted between by pure unit
+///  arithmetic. Months and datetimes are excluded: months need calendar
+///  arithmetic and datetimes are float-based.
+const CONVERTIBLE_TEMPORALS: [i8; 6] = [-12, -14, -16, -17, -18, -19];
+
+/// Numeric value of an atom lifted out of its q type, with nulls and
+///  infinities kept symbolic so a cast can map them onto the matching
+///  values of the target type.
+enum Scalar {
+  /// Typed null, e.g. `0N`.
+  Null,
+  /// Negative infinity, e.g. `-0W`.
+  NegativeInfinity,
+  /// Positive infinity, e.g. `0W`.
+  PositiveInfinity,
+  /// Ordinary integer value.
+  Int(i64),
+  /// Ordinary floating point value.
+  Float(f64),
+}
+
+/// Lift a short out of its q representation.
+fn lift_short(value: i16) -> Scalar {
+  match value {
+    i16::MIN => Scalar::Null,
+    v if v == i16::MIN + 1 => Scalar::NegativeInfinity,
+    i16::MAX => Scalar::PositiveInfinity,
+    v => Scalar::Int(v as i64),
+  }
+}
+
+/// Lift an int-backed value out of its q representation.
+fn lift_int(value: i32) -> Scalar {
+  match value {
+    i32::MIN => Scalar::Null,
+    v if v == i32::MIN + 1 => Scalar::NegativeInfinity,
+    i32::MAX => Scalar::PositiveInfinity,
+    v => Scalar::Int(v as i64),
+  }
+}
+
+/// Lift a long-backed value out of its q representation.
+fn lift_long(value: i64) -> Scalar {
+  match value {
+    i64::MIN => Scalar::Null,
+    v if v == i64::MIN + 1 => Scalar::NegativeInfinity,
+    i64::MAX => Scalar::PositiveInfinity,
+    v => Scalar::Int(v),
+  }
+}
+
+/// Lift a float-backed value out of its q representation.
+fn lift_float(value: f64) -> Scalar {
+  if value.is_nan() {
+    Scalar::Null
+  } else if value == f64::INFINITY {
+    Scalar::PositiveInfinity
+  } else if value == f64::NEG_INFINITY {
+    Scalar::NegativeInfinity
+  } else {
+    Scalar::Float(value)
+  }
+}
+
+/// Convert an ordinary temporal value between two of the unit-convertible
+///  temporal types, e.g. a timestamp into the date it falls on. `None` for
+///  a pair with no pure unit relationship.
+fn convert_temporal(value: i64, source: i8, target: i8) -> Option<i64> {
+  Some(match (source, target) {
+    (-12, -14) => value.div_euclid(DAY_NANOS),
+    (-12, -16) => value.rem_euclid(DAY_NANOS),
+    (-12, -19) => value.rem_euclid(DAY_NANOS) / 1_000_000,
+    (-12, -18) => value.rem_euclid(DAY_NANOS) / 1_000_000_000,
+    (-12, -17) => value.rem_euclid(DAY_NANOS) / 60_000_000_000,
+    (-14, -12) => value * DAY_NANOS,
+    (-16, -19) => value.div_euclid(1_000_000),
+    (-16, -18) => value.div_euclid(1_000_000_000),
+    (-16, -17) => value.div_euclid(60_000_000_000),
+    (-19, -16) => value * 1_000_000,
+    (-19, -18) => value.div_euclid(1000),
+    (-19, -17) => value.div_euclid(60_000),
+    (-18, -16) => value * 1_000_000_000,
+    (-18, -19) => value * 1000,
+    (-18, -17) => value.div_euclid(60),
+    (-17, -16) => value * 60_000_000_000,
+    (-17, -18) => value * 60,
+    (-17, -19) => value * 60_000,
+    _ => return None,
+  })
+}
+
+/// Realize a scalar as the underlying integer of a target type with the
+///  given null value and positive infinity, mapping the symbolic values
+///  onto their q representations and failing on an out of range value.
+fn int_value(scalar: Scalar, minimum: i64, maximum: i64, target: i8) -> io::Result<i64> {
+  let value = match scalar {
+    Scalar::Null => return Ok(minimum),
+    Scalar::NegativeInfinity => return Ok(minimum + 1),
+    Scalar::PositiveInfinity => return Ok(maximum),
+    Scalar::Int(value) => value,
+    Scalar::Float(value) => {
+      let rounded = value.round_ties_even();
+      if rounded < minimum as f64 || rounded > maximum as f64 {
+        return Err(cast_out_of_range(target));
+      }
+      return Ok(rounded as i64);
+    }
+  };
+  if value < minimum || value > maximum {
+    return Err(cast_out_of_range(target));
+  }
+  Ok(value)
+}
+
+/// Realize a scalar as a float, mapping the symbolic values onto NaN and
+///  the infinities.
+fn float_value(scalar: Scalar) -> f64 {
+  match scalar {
+    Scalar::Null => f64::NAN,
+    Scalar::NegativeInfinity => f64::NEG_INFINITY,
+    Scalar::PositiveInfinity => f64::INFINITY,
+    Scalar::Int(value) => value as f64,
+    Scalar::Float(value) => value,
+  }
+}
+
+/// Build the error returned for a cast between types with no defined
+///  conversion.
+fn unsupported_cast(object: &Q, target: i8) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::InvalidData,
+    format!(
+      "cannot cast q {} into type {}",
+      crate::convert::q_type_name(object),
+      target
+    ),
+  )
+}
+
+/// Build the error returned when a value does not fit the target type.
+fn cast_out_of_range(target: i8) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::InvalidData,
+    format!("cast into type {} out of range", target),
+  )
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn type_codes_match_q() {
+    assert_eq!(Q::Long(42).q_type(), -7);
+    assert_eq!(Q::LongList(QList::new(vec![42])).q_type(), 7);
+    assert_eq!(Q::MixedList(vec![]).q_type(), 0);
+    assert_eq!(
+      Q::Dictionary(QDictionary::sorted(Q::Null, Q::Null)).q_type(),
+      127
+    );
+    assert_eq!(Q::Function(QFunction::lambda("{x}")).q_type(), 100);
+    assert_eq!(Q::Null.q_type(), 101);
+  }
+
+  #[test]
+  fn lengths_follow_q_count_semantics() {
+    assert_eq!(Q::LongList(QList::new(vec![1, 2, 3])).len(), 3);
+    assert_eq!(Q::String("abc".to_string()).len(), 3);
+    assert_eq!(Q::Long(42).len(), 1);
+    assert_eq!(
+      Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+        Q::LongList(QList::new(vec![1, 2])),
+      ))
+      .len(),
+      2
+    );
+    let table = QTable::new(
+      vec!["sym".to_string()],
+      vec![Q::SymbolList(QList::new(vec!["a".to_string()]))],
+    )
+    .expect("table");
+    assert_eq!(Q::Table(table).len(), 1);
+    assert!(Q::MixedList(vec![]).is_empty());
+    assert!(!Q::Null.is_empty());
+  }
+
+  #[test]
+  fn lists_index_into_atoms() {
+    let list = Q::LongList(QList::new(vec![1, 2, 3]));
+    assert_eq!(list.get(1), Some(Q::Long(2)));
+    assert_eq!(list.get(3), None);
+    assert_eq!(Q::String("abc".to_string()).get(0), Some(Q::Char('a')));
+    assert_eq!(
+      Q::MixedList(vec![Q::Long(1), Q::Symbol("abc".to_string())]).get(1),
+      Some(Q::Symbol("abc".to_string()))
+    );
+    assert_eq!(Q::Long(42).get(0), None);
+    assert_eq!(list.slice(1..3), Some(Q::LongList(QList::new(vec![2, 3]))));
+    assert_eq!(list.slice(1..4), None);
+    assert_eq!(
+      Q::String("abc".to_string()).slice(0..2),
+      Some(Q::String("ab".to_string()))
+    );
+  }
+
+  #[test]
+  fn casts_follow_q_dollar_semantics() {
+    // Numeric narrowing and widening.
+    assert_eq!(Q::Long(42).cast(-6).expect("cast"), Q::Int(42));
+    assert_eq!(Q::Int(42).cast(-9).expect("cast"), Q::Float(42.0));
+    assert_eq!(Q::Float(4.5).cast(-7).expect("cast"), Q::Long(4));
+    assert!(Q::Long(1 << 40).cast(-6).is_err());
+    // Nulls and infinities map onto the target representation.
+    assert_eq!(Q::Long(i64::MIN).cast(-6).expect("cast"), Q::Int(i32::MIN));
+    assert_eq!(Q::Int(i32::MAX).cast(-9).expect("cast"), Q::Float(f64::INFINITY));
+    // A timestamp truncates to its date and keeps its time of day.
+    let noon_of_day_two = 2 * 86_400_000_000_000 + 43_200_000_000_000;
+    assert_eq!(
+      Q::Timestamp(noon_of_day_two).cast(-14).expect("cast"),
+      Q::Date(2)
+    );
+    assert_eq!(
+      Q::Timestamp(noon_of_day_two).cast(-19).expect("cast"),
+      Q::Time(43_200_000)
+    );
+    assert_eq!(Q::Minute(90).cast(-18).expect("cast"), Q::Second(5400));
+    // Months need calendar arithmetic, hence no cast from a date.
+    assert!(Q::Date(2).cast(-13).is_err());
+    // Lists cast element by element.
+    assert_eq!(
+      Q::LongList(QList::new(vec![1, 2])).cast(6).expect("cast"),
+      Q::IntList(QList::new(vec![1, 2]))
+    );
+  }
+
+  #[test]
+  fn lists_iterate_without_destructuring() {
+    let list = QList::new(vec![1_i64, 2, 3]);
+    assert_eq!(list.iter().sum::<i64>(), 6);
+    assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    let object = Q::LongList(QList::new(vec![1, 2, 3]));
+    assert_eq!(
+      object.iter().collect::<Vec<_>>(),
+      vec![Q::Long(1), Q::Long(2), Q::Long(3)]
+    );
+    assert_eq!(object.iter().len(), 3);
+    assert_eq!(Q::Long(42).iter().count(), 0);
+  }
+
+  #[test]
+  fn tables_iterate_record_by_record() {
+    let table = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+        Q::FloatList(QList::new(vec![1.0, 2.0])),
+      ],
+    )
+    .expect("table");
+    let mut rows = table.rows();
+    let first = rows.next().expect("first row");
+    assert_eq!(first.get::<String>("sym").expect("sym"), "a");
+    assert_eq!(first.get::<f64>("price").expect("price"), 1.0);
+    assert!(first.get::<f64>("size").is_err());
+    assert_eq!(rows.len(), 1);
+    let owned: Vec<_> = table.into_rows().collect();
+    assert_eq!(owned.len(), 2);
+    assert_eq!(owned[1].get::<String>("sym").expect("sym"), "b");
+  }
+
+  #[test]
+  fn columns_resolve_by_name() {
+    let mut table = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+        Q::FloatList(QList::new(vec![1.0, 2.0])),
+      ],
+    )
+    .expect("table");
+    assert_eq!(table.column_f64("price").expect("floats"), &[1.0, 2.0]);
+    assert_eq!(table.column_sym("sym").expect("symbols").len(), 2);
+    assert!(table.column("size").is_none());
+    assert!(table.column_i64("price").is_err());
+    if let Some(Q::FloatList(list)) = table.column_mut("price") {
+      list.data_mut()[0] = 1.5;
+    }
+    assert_eq!(table.column_f64("price").expect("floats")[0], 1.5);
+  }
+
+  #[test]
+  fn tables_reshape_in_place() {
+    let mut table = QTable::new(
+      vec!["sym".to_string()],
+      vec![Q::SymbolList(QList::new(vec![
+        "a".to_string(),
+        "b".to_string(),
+      ]))],
+    )
+    .expect("table");
+    table
+      .add_column("price", Q::FloatList(QList::new(vec![1.0, 2.0])))
+      .expect("add");
+    assert!(table
+      .add_column("size", Q::LongList(QList::new(vec![1])))
+      .is_err());
+    assert!(table.add_column("price", Q::FloatList(QList::new(vec![1.0, 2.0]))).is_err());
+    assert!(table.add_column("last", Q::Float(1.0)).is_err());
+    table.rename_column("price", "px").expect("rename");
+    assert!(table.rename_column("px", "sym").is_err());
+    assert_eq!(table.column_f64("px").expect("floats"), &[1.0, 2.0]);
+    let dropped = table.drop_column("px").expect("drop");
+    assert_eq!(dropped, Q::FloatList(QList::new(vec![1.0, 2.0])));
+    assert_eq!(table.columns(), &["sym".to_string()]);
+    assert!(table.drop_column("px").is_err());
+  }
+
+  #[test]
+  fn tables_grow_row_by_row() {
+    let mut table = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec!["a".to_string()])),
+        Q::FloatList(QList::new(vec![1.0])),
+      ],
+    )
+    .expect("table");
+    table
+      .push_row(vec![Q::Symbol("b".to_string()), Q::Float(2.0)])
+      .expect("push");
+    // A mismatched row leaves the table untouched.
+    assert!(table
+      .push_row(vec![Q::Symbol("c".to_string()), Q::Long(3)])
+      .is_err());
+    assert_eq!(table.row_count(), 2);
+    let other = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec!["c".to_string()])),
+        Q::FloatList(QList::new(vec![3.0])),
+      ],
+    )
+    .expect("table");
+    table.concat(other).expect("concat");
+    assert_eq!(table.row_count(), 3);
+    assert_eq!(table.column_f64("price").expect("floats"), &[1.0, 2.0, 3.0]);
+    let reordered = QTable::new(
+      vec!["price".to_string(), "sym".to_string()],
+      vec![
+        Q::FloatList(QList::new(vec![4.0])),
+        Q::SymbolList(QList::new(vec!["d".to_string()])),
+      ],
+    )
+    .expect("table");
+    assert!(table.concat(reordered).is_err());
+  }
+
+  #[test]
+  fn sorting_sets_the_sorted_attribute() {
+    let mut list = QList::new(vec![3_i64, 1, 2]);
+    list.sort();
+    assert_eq!(list.data(), &[1, 2, 3]);
+    assert_eq!(list.attribute(), Attribute::Sorted);
+
+    let mut table = QTable::new(
+      vec!["time".to_string(), "sym".to_string()],
+      vec![
+        Q::TimestampList(QList::new(vec![3, 1, 2])),
+        Q::SymbolList(QList::with_attribute(
+          vec!["c".to_string(), "a".to_string(), "b".to_string()],
+          Attribute::Grouped,
+        )),
+      ],
+    )
+    .expect("table");
+    table.sort_by("time").expect("sort");
+    assert_eq!(
+      *table.column("time").expect("time"),
+      Q::TimestampList(QList::with_attribute(vec![1, 2, 3], Attribute::Sorted))
+    );
+    // The payload columns follow the keys and lose their attributes.
+    assert_eq!(
+      *table.column("sym").expect("sym"),
+      Q::SymbolList(QList::new(vec![
+        "a".to_string(),
+        "b".to_string(),
+        "c".to_string(),
+      ]))
+    );
+    assert!(table.sort_by("price").is_err());
+  }
+
+  #[test]
+  fn tables_filter_and_slice_client_side() {
+    let table = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec![
+          "a".to_string(),
+          "b".to_string(),
+          "c".to_string(),
+        ])),
+        Q::FloatList(QList::with_attribute(
+          vec![1.0, 2.0, 3.0],
+          Attribute::Sorted,
+        )),
+      ],
+    )
+    .expect("table");
+    let expensive = table
+      .filter(|row| row.get::<f64>("price").is_ok_and(|price| price > 1.5))
+      .expect("filter");
+    assert_eq!(expensive.row_count(), 2);
+    // Dropping rows keeps the attributes of the surviving columns.
+    assert_eq!(
+      *expensive.column("price").expect("price"),
+      Q::FloatList(QList::with_attribute(vec![2.0, 3.0], Attribute::Sorted))
+    );
+    assert_eq!(table.take(2).expect("take").row_count(), 2);
+    assert_eq!(table.take(9).expect("take").row_count(), 3);
+    let middle = table.slice(1..2).expect("slice");
+    assert_eq!(middle.column_sym("sym").expect("symbols"), &["b".to_string()]);
+    assert!(table.slice(2..4).is_err());
+  }
+
+  #[test]
+  fn joins_enrich_tables_locally() {
+    let trades = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec![
+          "a".to_string(),
+          "b".to_string(),
+          "c".to_string(),
+        ])),
+        Q::FloatList(QList::new(vec![1.0, 2.0, 3.0])),
+      ],
+    )
+    .expect("trades");
+    let reference = QKeyedTable::xkey(
+      QTable::new(
+        vec!["sym".to_string(), "ccy".to_string()],
+        vec![
+          Q::SymbolList(QList::new(vec!["a".to_string(), "c".to_string()])),
+          Q::SymbolList(QList::new(vec!["usd".to_string(), "eur".to_string()])),
+        ],
+      )
+      .expect("reference"),
+      &["sym"],
+    )
+    .expect("xkey");
+    let left = trades.lj(&reference).expect("lj");
+    assert_eq!(left.row_count(), 3);
+    assert_eq!(
+      *left.column("ccy").expect("ccy"),
+      Q::SymbolList(QList::new(vec![
+        "usd".to_string(),
+        String::new(),
+        "eur".to_string(),
+      ]))
+    );
+    let inner = trades.ij(&reference).expect("ij");
+    assert_eq!(inner.row_count(), 2);
+    assert_eq!(
+      *inner.column("price").expect("price"),
+      Q::FloatList(QList::new(vec![1.0, 3.0]))
+    );
+    assert_eq!(
+      reference.clone().unkey().columns(),
+      &["sym".to_string(), "ccy".to_string()]
+    );
+  }
+
+  #[test]
+  fn keyed_tables_look_up_rows_by_key() {
+    let reference = QKeyedTable::xkey(
+      QTable::new(
+        vec!["sym".to_string(), "ccy".to_string(), "lot".to_string()],
+        vec![
+          Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+          Q::SymbolList(QList::new(vec!["usd".to_string(), "eur".to_string()])),
+          Q::LongList(QList::new(vec![100, 10])),
+        ],
+      )
+      .expect("reference"),
+      &["sym"],
+    )
+    .expect("xkey");
+    let key = [Q::Symbol("b".to_string())];
+    assert!(reference.contains_key(&key).expect("arity"));
+    let row = reference.get_row(&key).expect("arity").expect("present");
+    assert_eq!(row.get::<String>("ccy").expect("ccy"), "eur");
+    assert_eq!(row.get::<i64>("lot").expect("lot"), 10);
+    assert!(reference
+      .get_row(&[Q::Symbol("c".to_string())])
+      .expect("arity")
+      .is_none());
+    assert!(reference.get_row(&[]).is_err());
+  }
+
+  #[test]
+  fn asof_joins_pick_the_prevailing_quote() {
+    let trades = QTable::new(
+      vec!["sym".to_string(), "time".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec![
+          "a".to_string(),
+          "a".to_string(),
+          "b".to_string(),
+        ])),
+        Q::TimeList(QList::new(vec![100, 250, 50])),
+        Q::FloatList(QList::new(vec![1.0, 2.0, 3.0])),
+      ],
+    )
+    .expect("trades");
+    let quotes = QTable::new(
+      vec!["sym".to_string(), "time".to_string(), "bid".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec![
+          "a".to_string(),
+          "a".to_string(),
+          "b".to_string(),
+        ])),
+        Q::TimeList(QList::new(vec![200, 90, 60])),
+        Q::FloatList(QList::new(vec![1.9, 0.9, 2.9])),
+      ],
+    )
+    .expect("quotes");
+    let joined = trades.aj(&["sym", "time"], &quotes).expect("aj");
+    // The first trade takes the 90ms quote, the second the 200ms quote and
+    //  the third precedes every quote of its symbol.
+    let Q::FloatList(bids) = joined.column("bid").expect("bid") else {
+      panic!("bid is not a float list");
+    };
+    assert_eq!(bids.data()[..2], [0.9, 1.9]);
+    assert!(bids.data()[2].is_nan());
+    // The time column stays the trade time.
+    assert_eq!(
+      *joined.column("time").expect("time"),
+      Q::TimeList(QList::new(vec![100, 250, 50]))
+    );
+  }
+
+  #[test]
+  fn xbar_truncates_to_bar_boundaries() {
+    assert_eq!(
+      Q::Time(61_500).xbar(60_000).expect("time"),
+      Q::Time(60_000)
+    );
+    assert_eq!(
+      Q::TimestampList(QList::with_attribute(
+        vec![1_500_000_000, 2_999_999_999, i64::MIN],
+        Attribute::Sorted
+      ))
+      .xbar(1_000_000_000)
+      .expect("timestamps"),
+      Q::TimestampList(QList::with_attribute(
+        vec![1_000_000_000, 2_000_000_000, i64::MIN],
+        Attribute::Sorted
+      ))
+    );
+    let mut table = QTable::new(
+      vec!["time".to_string(), "price".to_string()],
+      vec![
+        Q::TimeList(QList::new(vec![100, 59_999, 60_001])),
+        Q::FloatList(QList::new(vec![1.0, 2.0, 3.0])),
+      ],
+    )
+    .expect("table");
+    table
+      .bucket_by_time("time", std::time::Duration::from_secs(60))
+      .expect("bucket");
+    assert_eq!(
+      *table.column("time").expect("time"),
+      Q::TimeList(QList::new(vec![0, 0, 60_000]))
+    );
+    assert!(Q::Symbol("a".to_string()).xbar(60).is_err());
+  }
+
+  #[test]
+  fn dictionaries_convert_to_maps_and_back() {
+    let dictionary = QDictionary::from_pairs(vec![("fast", 100_i64), ("slow", 10)]);
+    assert_eq!(
+      *dictionary.keys(),
+      Q::SymbolList(QList::new(vec!["fast".to_string(), "slow".to_string()]))
+    );
+    assert_eq!(
+      dictionary.get(&Q::Symbol("slow".to_string())),
+      Some(Q::Long(10))
+    );
+    assert_eq!(dictionary.get(&Q::Symbol("off".to_string())), None);
+    let map = dictionary
+      .clone()
+      .into_hashmap::<String, i64>()
+      .expect("hashmap");
+    assert_eq!(map.get("fast"), Some(&100));
+    let map = dictionary.into_btreemap::<String, i64>().expect("btreemap");
+    assert_eq!(
+      map.into_iter().collect::<Vec<(String, i64)>>(),
+      vec![("fast".to_string(), 100), ("slow".to_string(), 10)]
+    );
+  }
+
+  #[test]
+  fn schemas_extract_and_validate() {
+    let table = QTable::new(
+      vec!["sym".to_string(), "price".to_string()],
+      vec![
+        Q::SymbolList(QList::new(vec!["a".to_string()])),
+        Q::FloatList(QList::new(vec![1.0])),
+      ],
+    )
+    .expect("table");
+    let schema = table.schema();
+    assert_eq!(
+      schema,
+      QSchema::new(vec![
+        ("sym".to_string(), QType::Symbol),
+        ("price".to_string(), QType::Float),
+      ])
+    );
+    schema.validate(&table).expect("the table matches itself");
+    let other = QSchema::new(vec![
+      ("sym".to_string(), QType::Symbol),
+      ("price".to_string(), QType::Long),
+    ]);
+    let error = other.validate(&table).expect_err("float is not long");
+    assert!(error.to_string().contains("column 'price'"));
+  }
+
+  #[test]
+  fn kind_predicates_branch_without_matching() {
+    assert!(Q::Symbol("abc".to_string()).is_atom());
+    assert!(!Q::Error("type".to_string()).is_atom());
+    assert!(Q::String("abc".to_string()).is_list());
+    assert!(Q::MixedList(vec![]).is_list());
+    assert!(!Q::Null.is_list());
+    assert!(Q::Error("type".to_string()).is_error());
+    assert!(Q::Long(i64::MIN).is_null_atom());
+    assert!(Q::Float(f64::NAN).is_null_atom());
+    assert!(!Q::Long(0).is_null_atom());
+    assert!(Q::Null.is_null_atom());
+  }
+
+  #[test]
+  fn infinity_predicates_cover_every_numeric_type() {
+    assert!(Q::Long(i64::MAX).is_pos_inf());
+    assert!(Q::Long(i64::MIN + 1).is_neg_inf());
+    assert!(Q::Float(f64::INFINITY).is_pos_inf());
+    assert!(Q::Float(f64::NEG_INFINITY).is_neg_inf());
+    assert!(Q::Timestamp(i64::MAX).is_pos_inf());
+    assert!(Q::Guid([0; 16]).is_null());
+    assert!(!Q::Long(42).is_pos_inf());
+    assert!(!Q::Long(42).is_neg_inf());
+    assert!(!Q::Symbol("abc".to_string()).is_null());
+  }
+}
@@ -0,0 +1,716 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Builders for compound-list functional queries.
+//!
+//! kdb+ evaluates an inbound mixed list as a function application: the
+//! first element names the function (a symbol) or is an expression to
+//! evaluate (a char list, e.g. an operator), and the remaining elements
+//! are its arguments. [`QExpr`] composes those lists fluently instead of
+//! assembling `Q::MixedList` by hand.
+//!
+//! # Example
+//! ```
+//! use rustkdb::query::QExpr;
+//! use rustkdb::qtype::Q;
+//!
+//! // (`post_order; `AAPL; 100)
+//! let call = QExpr::func("post_order").arg("AAPL").arg(100i64).build();
+//! assert_eq!(
+//!   call,
+//!   Q::MixedList(vec![
+//!     Q::Symbol("post_order".to_string()),
+//!     Q::Symbol("AAPL".to_string()),
+//!     Q::Long(100),
+//!   ])
+//! );
+//!
+//! // ("+"; 1; ("*"; 2; 3)) — nested calls are arguments like any other.
+//! let nested = QExpr::operator("+")
+//!   .arg(1i64)
+//!   .arg(QExpr::operator("*").arg(2i64).arg(3i64))
+//!   .build();
+//! ```
+//!
+//! [`Select`], [`Update`] and [`Delete`] build the qSQL functional forms
+//! `?[t;c;b;a]` and `![t;c;b;a]` from column expressions ([`col`], the
+//! comparison methods on [`ColExpr`] and aggregates like [`avg`]):
+//!
+//! ```
+//! use rustkdb::query::{avg, col, Select};
+//!
+//! // select avg price by sym from trade where sym=`AAPL
+//! let query = Select::from("trade")
+//!   .where_(col("sym").eq("AAPL"))
+//!   .by("sym")
+//!   .agg(avg("price"))
+//!   .build();
+//! ```
+//!
+//! Functions inside the parse trees are carried as char lists and resolved
+//! with `value` by a small lambda wrapped around the call, since primitive
+//! function atoms cannot be sent over IPC from a client.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use crate::convert::IntoQ;
+use crate::qtype::{Q, QDictionary, QFunction, QList};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% QExpr %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Builder for one functional query: a function followed by its
+///  arguments, sent as a mixed list.
+#[derive(Clone, Debug)]
+pub struct QExpr {
+  /// Function followed by its arguments so far.
+  items: Vec<Q>,
+}
+
+impl QExpr {
+  /// Start a call of a named function, e.g. `.u.upd` or a user-defined
+  ///  function on the remote process.
+  /// # Parameters
+  /// - `name`: Name of the function, sent as a symbol.
+  pub fn func(name: &str) -> Self {
+    QExpr {
+      items: vec![Q::Symbol(name.to_string())],
+    }
+  }
+
+  /// Start a call of a built-in operator or any q expression, e.g. `"+"`,
+  ///  `"within"` or `"{x+y}"`, sent as a char list for the remote process
+  ///  to evaluate before applying.
+  /// # Parameters
+  /// - `operator`: Operator or expression text.
+  pub fn operator(operator: &str) -> Self {
+    QExpr {
+      items: vec![Q::String(operator.to_string())],
+    }
+  }
+
+  /// Start a call of an inline lambda, sent as an actual function object
+  ///  (type 100h) rather than text, e.g. for one-off logic the remote
+  ///  process has no named function for.
+  /// # Parameters
+  /// - `body`: Definition text, e.g. `"{x+y}"`.
+  pub fn lambda(body: &str) -> Self {
+    QExpr {
+      items: vec![Q::Function(QFunction::lambda(body))],
+    }
+  }
+
+  /// Append an argument. Nested calls are arguments too: a [`QExpr`]
+  ///  argument is built into its mixed list in place.
+  /// # Parameters
+  /// - `argument`: Any value convertible into a q object.
+  pub fn arg<T: IntoQ>(mut self, argument: T) -> Self {
+    self.items.push(argument.into_q());
+    self
+  }
+
+  /// Append an already-built q object as an argument.
+  /// # Parameters
+  /// - `argument`: Object to pass through unchanged.
+  pub fn arg_q(mut self, argument: Q) -> Self {
+    self.items.push(argument);
+    self
+  }
+
+  /// Leave the next argument open with the generic null, turning the call
+  ///  into a projection the remote process can apply later.
+  pub fn hole(mut self) -> Self {
+    self.items.push(Q::Null);
+    self
+  }
+
+  /// Build the compound list, ready for
+  ///  [`send_query`](crate::connection::Handle::send_query).
+  pub fn build(self) -> Q {
+    Q::MixedList(self.items)
+  }
+}
+
+impl IntoQ for QExpr {
+  fn into_q(self) -> Q {
+    self.build()
+  }
+}
+
+//%% ColExpr %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// One column expression as a qSQL parse tree: a column reference, a
+///  comparison or an aggregate. Function heads are char lists resolved
+///  with `value` on the remote side.
+#[derive(Clone, Debug)]
+pub struct ColExpr {
+  /// Parse tree of the expression.
+  tree: Q,
+}
+
+/// Reference a column by name.
+/// # Parameters
+/// - `name`: Column name, e.g. `"sym"`.
+pub fn col(name: &str) -> ColExpr {
+  ColExpr {
+    tree: Q::Symbol(name.to_string()),
+  }
+}
+
+/// Apply any unary function to a column expression, e.g.
+///  `apply("deltas", col("price"))`.
+/// # Parameters
+/// - `function`: Name of the function or an operator.
+/// - `argument`: Expression the function is applied to.
+pub fn apply(function: &str, argument: ColExpr) -> ColExpr {
+  ColExpr {
+    tree: Q::MixedList(vec![Q::String(function.to_string()), argument.tree]),
+  }
+}
+
+/// Average of a column, `avg price`.
+pub fn avg(column: &str) -> ColExpr {
+  apply("avg", col(column))
+}
+
+/// Sum of a column, `sum price`.
+pub fn sum(column: &str) -> ColExpr {
+  apply("sum", col(column))
+}
+
+/// Count of a column, `count price`.
+pub fn count(column: &str) -> ColExpr {
+  apply("count", col(column))
+}
+
+/// Minimum of a column, `min price`.
+pub fn min(column: &str) -> ColExpr {
+  apply("min", col(column))
+}
+
+/// Maximum of a column, `max price`.
+pub fn max(column: &str) -> ColExpr {
+  apply("max", col(column))
+}
+
+/// First value of a column, `first price`.
+pub fn first(column: &str) -> ColExpr {
+  apply("first", col(column))
+}
+
+/// Last value of a column, `last price`.
+pub fn last(column: &str) -> ColExpr {
+  apply("last", col(column))
+}
+
+impl ColExpr {
+  /// Equality, `column = value`.
+  pub fn eq<T: IntoQ>(self, value: T) -> ColExpr {
+    self.compare("=", value)
+  }
+
+  /// Inequality, `column <> value`.
+  pub fn ne<T: IntoQ>(self, value: T) -> ColExpr {
+    self.compare("<>", value)
+  }
+
+  /// Strictly less, `column < value`.
+  pub fn lt<T: IntoQ>(self, value: T) -> ColExpr {
+    self.compare("<", value)
+  }
+
+  /// At most, `column <= value`.
+  pub fn le<T: IntoQ>(self, value: T) -> ColExpr {
+    self.compare("<=", value)
+  }
+
+  /// Strictly greater, `column > value`.
+  pub fn gt<T: IntoQ>(self, value: T) -> ColExpr {
+    self.compare(">", value)
+  }
+
+  /// At least, `column >= value`.
+  pub fn ge<T: IntoQ>(self, value: T) -> ColExpr {
+    self.compare(">=", value)
+  }
+
+  /// Membership, `column in values`.
+  /// # Parameters
+  /// - `values`: List of candidates, e.g. `vec!["AAPL", "MSFT"]`.
+  pub fn in_list<T: IntoQ>(self, values: T) -> ColExpr {
+    self.compare("in", values)
+  }
+
+  /// Interval test, `column within (low; high)`.
+  pub fn within<T: IntoQ>(self, low: T, high: T) -> ColExpr {
+    ColExpr {
+      tree: Q::MixedList(vec![
+        Q::String("within".to_string()),
+        self.tree,
+        Q::MixedList(vec![literal(low.into_q()), literal(high.into_q())]),
+      ]),
+    }
+  }
+
+  /// Pattern match, `column like pattern`.
+  pub fn like(self, pattern: &str) -> ColExpr {
+    ColExpr {
+      tree: Q::MixedList(vec![
+        Q::String("like".to_string()),
+        self.tree,
+        Q::String(pattern.to_string()),
+      ]),
+    }
+  }
+
+  /// Both expressions hold.
+  pub fn and(self, other: ColExpr) -> ColExpr {
+    ColExpr {
+      tree: Q::MixedList(vec![Q::String("&".to_string()), self.tree, other.tree]),
+    }
+  }
+
+  /// Either expression holds.
+  pub fn or(self, other: ColExpr) -> ColExpr {
+    ColExpr {
+      tree: Q::MixedList(vec![Q::String("|".to_string()), self.tree, other.tree]),
+    }
+  }
+
+  /// Build the comparison parse tree, enlisting symbol literals so they
+  ///  are not read as column names.
+  fn compare<T: IntoQ>(self, operator: &str, value: T) -> ColExpr {
+    ColExpr {
+      tree: Q::MixedList(vec![
+        Q::String(operator.to_string()),
+        self.tree,
+        literal(value.into_q()),
+      ]),
+    }
+  }
+}
+
+impl IntoQ for ColExpr {
+  fn into_q(self) -> Q {
+    self.tree
+  }
+}
+
+//%% Select %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Lambda wrapped around `?[t;c;b;a]`: resolves the char-list function
+///  heads inside the parse trees with `value`, then applies the form.
+const FUNCTIONAL_SELECT: &str = "{[t;c;b;a] f:{$[0h<>type x; x; 10h=type first x; (value first x),.z.s'[1_x]; .z.s'[x]]}; ?[t; f'[c]; $[99h=type b; f'[b]; b]; $[99h=type a; f'[a]; a]]}";
+
+/// The same lambda around `![t;c;b;a]` for updates and deletes.
+const FUNCTIONAL_AMEND: &str = "{[t;c;b;a] f:{$[0h<>type x; x; 10h=type first x; (value first x),.z.s'[1_x]; .z.s'[x]]}; ![t; f'[c]; $[99h=type b; f'[b]; b]; $[99h=type a; f'[a]; a]]}";
+
+/// Builder for the functional form of `select`, `?[t;c;b;a]`.
+#[derive(Clone, Debug)]
+pub struct Select {
+  /// Table the query runs against.
+  table: String,
+  /// Where-clause parse trees, one per `where_` call.
+  constraints: Vec<Q>,
+  /// Grouping columns: result name and grouping expression.
+  groupings: Vec<(String, Q)>,
+  /// Selected columns: result name and value expression.
+  columns: Vec<(String, Q)>,
+}
+
+impl Select {
+  /// Start a select against a table.
+  /// # Parameters
+  /// - `table`: Name of the table.
+  pub fn from(table: &str) -> Self {
+    Select {
+      table: table.to_string(),
+      constraints: Vec::new(),
+      groupings: Vec::new(),
+      columns: Vec::new(),
+    }
+  }
+
+  /// Add a where clause; multiple clauses are applied in order, each one
+  ///  narrowing the rows the next one sees.
+  pub fn where_(mut self, constraint: ColExpr) -> Self {
+    self.constraints.push(constraint.tree);
+    self
+  }
+
+  /// Group by a column.
+  pub fn by(mut self, column: &str) -> Self {
+    self.groupings.push((column.to_string(), Q::Symbol(column.to_string())));
+    self
+  }
+
+  /// Group by an expression under an explicit result name.
+  pub fn by_as(mut self, name: &str, expression: ColExpr) -> Self {
+    self.groupings.push((name.to_string(), expression.tree));
+    self
+  }
+
+  /// Select a plain column.
+  pub fn select(mut self, column: &str) -> Self {
+    self.columns.push((column.to_string(), Q::Symbol(column.to_string())));
+    self
+  }
+
+  /// Select an aggregate or computed column, named after the first column
+  ///  it mentions — as qSQL itself names `avg price` just `price`.
+  pub fn agg(mut self, expression: ColExpr) -> Self {
+    let name = derived_column_name(&expression.tree);
+    self.columns.push((name, expression.tree));
+    self
+  }
+
+  /// Select an aggregate or computed column under an explicit result name.
+  pub fn agg_as(mut self, name: &str, expression: ColExpr) -> Self {
+    self.columns.push((name.to_string(), expression.tree));
+    self
+  }
+
+  /// Build the functional call, ready for
+  ///  [`send_query`](crate::connection::Handle::send_query). An empty
+  ///  column list selects all columns.
+  pub fn build(self) -> Q {
+    Q::MixedList(vec![
+      Q::String(FUNCTIONAL_SELECT.to_string()),
+      Q::Symbol(self.table),
+      Q::MixedList(self.constraints),
+      grouping_argument(self.groupings),
+      clause_dictionary(self.columns),
+    ])
+  }
+}
+
+//%% Update %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Builder for the functional form of `update`, `![t;c;b;a]`.
+#[derive(Clone, Debug)]
+pub struct Update {
+  /// Table the update runs against.
+  table: String,
+  /// Where-clause parse trees.
+  constraints: Vec<Q>,
+  /// Grouping columns: result name and grouping expression.
+  groupings: Vec<(String, Q)>,
+  /// Assignments: column name and value expression.
+  assignments: Vec<(String, Q)>,
+}
+
+impl Update {
+  /// Start an update against a table.
+  /// # Parameters
+  /// - `table`: Name of the table.
+  pub fn table(table: &str) -> Self {
+    Update {
+      table: table.to_string(),
+      constraints: Vec::new(),
+      groupings: Vec::new(),
+      assignments: Vec::new(),
+    }
+  }
+
+  /// Add a where clause restricting the rows being updated.
+  pub fn where_(mut self, constraint: ColExpr) -> Self {
+    self.constraints.push(constraint.tree);
+    self
+  }
+
+  /// Group by a column, as in `update ... by sym from t`.
+  pub fn by(mut self, column: &str) -> Self {
+    self.groupings.push((column.to_string(), Q::Symbol(column.to_string())));
+    self
+  }
+
+  /// Assign a column from an expression, `column: expression`.
+  pub fn set<T: IntoQ>(mut self, column: &str, expression: T) -> Self {
+    self.assignments.push((column.to_string(), expression.into_q()));
+    self
+  }
+
+  /// Build the functional call.
+  pub fn build(self) -> Q {
+    Q::MixedList(vec![
+      Q::String(FUNCTIONAL_AMEND.to_string()),
+      Q::Symbol(self.table),
+      Q::MixedList(self.constraints),
+      grouping_argument(self.groupings),
+      clause_dictionary(self.assignments),
+    ])
+  }
+}
+
+//%% Delete %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Builder for the functional form of `delete`, `![t;c;0b;a]`: either
+///  rows selected by where clauses, or whole columns — not both, as in
+///  qSQL itself.
+#[derive(Clone, Debug)]
+pub struct Delete {
+  /// Table the delete runs against.
+  table: String,
+  /// Where-clause parse trees selecting the rows to delete.
+  constraints: Vec<Q>,
+  /// Columns to delete; takes the place of the where clauses.
+  columns: Vec<String>,
+}
+
+impl Delete {
+  /// Start a delete against a table.
+  /// # Parameters
+  /// - `table`: Name of the table.
+  pub fn from(table: &str) -> Self {
+    Delete {
+      table: table.to_string(),
+      constraints: Vec::new(),
+      columns: Vec::new(),
+    }
+  }
+
+  /// Delete the rows matching a where clause.
+  pub fn where_(mut self, constraint: ColExpr) -> Self {
+    self.constraints.push(constraint.tree);
+    self
+  }
+
+  /// Delete a whole column instead of rows; any where clauses are ignored
+  ///  once a column is named, matching the `![t;();0b;cols]` form.
+  pub fn column(mut self, column: &str) -> Self {
+    self.columns.push(column.to_string());
+    self
+  }
+
+  /// Build the functional call.
+  pub fn build(self) -> Q {
+    let (constraints, target) = if self.columns.is_empty() {
+      // Deleting rows: a is the empty symbol list.
+      (self.constraints, Q::SymbolList(QList::new(Vec::new())))
+    } else {
+      (Vec::new(), Q::SymbolList(QList::new(self.columns)))
+    };
+    Q::MixedList(vec![
+      Q::String(FUNCTIONAL_AMEND.to_string()),
+      Q::Symbol(self.table),
+      Q::MixedList(constraints),
+      Q::Bool(false),
+      target,
+    ])
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Enlist symbol literals so the parse trees read them as data rather
+///  than as column names; anything else is already a literal.
+fn literal(object: Q) -> Q {
+  match object {
+    Q::Symbol(symbol) => Q::SymbolList(QList::new(vec![symbol])),
+    Q::SymbolList(list) => Q::MixedList(vec![Q::SymbolList(list)]),
+    other => other,
+  }
+}
+
+/// Name a computed column after the first column it mentions, as qSQL
+///  names `avg price` just `price`.
+fn derived_column_name(tree: &Q) -> String {
+  match tree {
+    Q::Symbol(column) => column.clone(),
+    Q::MixedList(items) => items
+      .iter()
+      .skip(1)
+      .find_map(|item| match derived_column_name(item).as_str() {
+        "x" => None,
+        name => Some(name.to_string()),
+      })
+      .unwrap_or_else(|| "x".to_string()),
+    _ => "x".to_string(),
+  }
+}
+
+/// Build the `b` argument: `0b` without groupings, otherwise the
+///  dictionary of grouping expressions.
+fn grouping_argument(groupings: Vec<(String, Q)>) -> Q {
+  if groupings.is_empty() {
+    Q::Bool(false)
+  } else {
+    clause_dictionary(groupings)
+  }
+}
+
+/// Build a name!expression dictionary for the `b` and `a` arguments; an
+///  empty clause becomes the empty list, selecting all columns.
+fn clause_dictionary(clauses: Vec<(String, Q)>) -> Q {
+  if clauses.is_empty() {
+    return Q::MixedList(Vec::new());
+  }
+  let (names, expressions): (Vec<String>, Vec<Q>) = clauses.into_iter().unzip();
+  Q::Dictionary(QDictionary::new(
+    Q::SymbolList(QList::new(names)),
+    Q::MixedList(expressions),
+  ))
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn lambda_heads_are_function_objects() {
+    let call = QExpr::lambda("{x+y}").arg(1_i64).arg(2_i64).build();
+    assert_eq!(
+      call,
+      Q::MixedList(vec![
+        Q::Function(QFunction::lambda("{x+y}")),
+        Q::Long(1),
+        Q::Long(2),
+      ])
+    );
+    // A projection fixes the first argument and leaves the second open.
+    let projection =
+      QFunction::projection(Q::Function(QFunction::lambda("{x+y}")), vec![Q::Long(1), Q::Null]);
+    assert_eq!(
+      projection,
+      QFunction::Projection(vec![
+        Q::Function(QFunction::lambda("{x+y}")),
+        Q::Long(1),
+        Q::Null,
+      ])
+    );
+    assert_eq!(
+      QFunction::lambda_in("u", "{x}"),
+      QFunction::Lambda {
+        context: "u".to_string(),
+        body: "{x}".to_string(),
+      }
+    );
+  }
+
+  #[test]
+  fn expressions_compose_into_mixed_lists() {
+    let call = QExpr::func("post_order")
+      .arg("AAPL")
+      .arg(vec![100_i64, 200])
+      .build();
+    assert_eq!(
+      call,
+      Q::MixedList(vec![
+        Q::Symbol("post_order".to_string()),
+        Q::Symbol("AAPL".to_string()),
+        Q::LongList(crate::qtype::QList::new(vec![100, 200])),
+      ])
+    );
+
+    let nested = QExpr::operator("+")
+      .arg(1_i64)
+      .arg(QExpr::operator("*").arg(2_i64).arg(3_i64))
+      .build();
+    assert_eq!(
+      nested,
+      Q::MixedList(vec![
+        Q::String("+".to_string()),
+        Q::Long(1),
+        Q::MixedList(vec![Q::String("*".to_string()), Q::Long(2), Q::Long(3)]),
+      ])
+    );
+
+    let projection = QExpr::operator("within").hole().arg_q(Q::Null).build();
+    assert_eq!(
+      projection,
+      Q::MixedList(vec![Q::String("within".to_string()), Q::Null, Q::Null])
+    );
+  }
+
+  #[test]
+  fn select_builds_the_functional_form() {
+    let query = Select::from("trade")
+      .where_(col("sym").eq("AAPL"))
+      .by("sym")
+      .agg(avg("price"))
+      .build();
+    let Q::MixedList(items) = query else {
+      panic!("expected a mixed list");
+    };
+    assert_eq!(items.len(), 5);
+    assert_eq!(items[0], Q::String(FUNCTIONAL_SELECT.to_string()));
+    assert_eq!(items[1], Q::Symbol("trade".to_string()));
+    // Symbol literals are enlisted so they are not read as column names.
+    assert_eq!(
+      items[2],
+      Q::MixedList(vec![Q::MixedList(vec![
+        Q::String("=".to_string()),
+        Q::Symbol("sym".to_string()),
+        Q::SymbolList(QList::new(vec!["AAPL".to_string()])),
+      ])])
+    );
+    assert_eq!(
+      items[3],
+      Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(vec!["sym".to_string()])),
+        Q::MixedList(vec![Q::Symbol("sym".to_string())]),
+      ))
+    );
+    // The aggregate is named after the column it mentions.
+    assert_eq!(
+      items[4],
+      Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(vec!["price".to_string()])),
+        Q::MixedList(vec![Q::MixedList(vec![
+          Q::String("avg".to_string()),
+          Q::Symbol("price".to_string()),
+        ])]),
+      ))
+    );
+  }
+
+  #[test]
+  fn update_and_delete_build_the_amend_form() {
+    let update = Update::table("trade")
+      .where_(col("size").gt(1000_i64).and(col("sym").ne("MSFT")))
+      .set("price", apply("abs", col("price")))
+      .build();
+    let Q::MixedList(items) = update else {
+      panic!("expected a mixed list");
+    };
+    assert_eq!(items[0], Q::String(FUNCTIONAL_AMEND.to_string()));
+    assert_eq!(
+      items[4],
+      Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(vec!["price".to_string()])),
+        Q::MixedList(vec![Q::MixedList(vec![
+          Q::String("abs".to_string()),
+          Q::Symbol("price".to_string()),
+        ])]),
+      ))
+    );
+
+    let rows = Delete::from("trade").where_(col("size").eq(0_i64)).build();
+    let Q::MixedList(items) = rows else {
+      panic!("expected a mixed list");
+    };
+    assert_eq!(items[4], Q::SymbolList(QList::new(Vec::new())));
+
+    let columns = Delete::from("trade").column("venue").build();
+    let Q::MixedList(items) = columns else {
+      panic!("expected a mixed list");
+    };
+    assert_eq!(items[2], Q::MixedList(Vec::new()));
+    assert_eq!(
+      items[4],
+      Q::SymbolList(QList::new(vec!["venue".to_string()]))
+    );
+  }
+}
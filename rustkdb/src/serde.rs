@@ -0,0 +1,959 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! serde support: convert any `Serialize`/`Deserialize` type to and from
+//! [`Q`] objects.
+//!
+//! [`to_q`] runs a value through a `serde::Serializer` producing a [`Q`]
+//! object — structs and maps become dictionaries, sequences become lists
+//! (condensed into the matching simple list when homogeneous) — and
+//! [`from_q`] drives a `serde::Deserializer` over a [`Q`] object, so
+//! existing serde data models work against q without a parallel set of
+//! conversions. Tables deserialize as sequences of row dictionaries.
+//!
+//! Enabled with the `serde` cargo feature.
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Trade {
+//!   sym: String,
+//!   price: f64,
+//! }
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let trade = Trade {
+//!   sym: "abc".to_string(),
+//!   price: 102.5,
+//! };
+//! let object = rustkdb::serde::to_q(&trade)?;
+//! let back: Trade = rustkdb::serde::from_q(object)?;
+//! assert_eq!(back, trade);
+//! # Ok(())}
+//! ```
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::fmt;
+use std::io;
+
+use serde::de::{
+  DeserializeOwned, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+  VariantAccess, Visitor,
+};
+use serde::ser::{
+  Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+  SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+use crate::qtype::{Q, QDictionary, QList};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% Error %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Error produced while converting through serde, turned into an
+///  `io::Error` of kind `InvalidData` at the public functions.
+#[derive(Debug)]
+struct Error(String);
+
+impl fmt::Display for Error {
+  fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+    formatter.write_str(&self.0)
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+  fn custom<T: fmt::Display>(message: T) -> Self {
+    Error(message.to_string())
+  }
+}
+
+impl serde::de::Error for Error {
+  fn custom<T: fmt::Display>(message: T) -> Self {
+    Error(message.to_string())
+  }
+}
+
+impl From<Error> for io::Error {
+  fn from(error: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.0)
+  }
+}
+
+//%% QSerializer %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// serde serializer producing a [`Q`] object.
+struct QSerializer;
+
+/// In-progress sequence, tuple or tuple struct.
+struct SeqSerializer {
+  /// Serialized elements.
+  items: Vec<Q>,
+}
+
+/// In-progress tuple or struct variant, wrapped into a single-entry
+///  dictionary keyed by the variant name.
+struct VariantSerializer {
+  /// Name of the enum variant.
+  variant: &'static str,
+  /// Serialized elements or field values.
+  items: Vec<Q>,
+  /// Field names when serializing a struct variant.
+  keys: Vec<String>,
+}
+
+/// In-progress map.
+struct MapSerializer {
+  /// Serialized keys; char list keys are turned into symbols.
+  keys: Vec<Q>,
+  /// Serialized values.
+  values: Vec<Q>,
+}
+
+/// In-progress struct.
+struct StructSerializer {
+  /// Field names.
+  keys: Vec<String>,
+  /// Serialized field values.
+  values: Vec<Q>,
+}
+
+impl Serializer for QSerializer {
+  type Ok = Q;
+  type Error = Error;
+  type SerializeSeq = SeqSerializer;
+  type SerializeTuple = SeqSerializer;
+  type SerializeTupleStruct = SeqSerializer;
+  type SerializeTupleVariant = VariantSerializer;
+  type SerializeMap = MapSerializer;
+  type SerializeStruct = StructSerializer;
+  type SerializeStructVariant = VariantSerializer;
+
+  fn serialize_bool(self, value: bool) -> Result<Q, Error> {
+    Ok(Q::Bool(value))
+  }
+
+  /// q has no signed byte; `i8` widens into a short.
+  fn serialize_i8(self, value: i8) -> Result<Q, Error> {
+    Ok(Q::Short(i16::from(value)))
+  }
+
+  fn serialize_i16(self, value: i16) -> Result<Q, Error> {
+    Ok(Q::Short(value))
+  }
+
+  fn serialize_i32(self, value: i32) -> Result<Q, Error> {
+    Ok(Q::Int(value))
+  }
+
+  fn serialize_i64(self, value: i64) -> Result<Q, Error> {
+    Ok(Q::Long(value))
+  }
+
+  fn serialize_u8(self, value: u8) -> Result<Q, Error> {
+    Ok(Q::Byte(value))
+  }
+
+  /// Unsigned types widen into the next signed q type.
+  fn serialize_u16(self, value: u16) -> Result<Q, Error> {
+    Ok(Q::Int(i32::from(value)))
+  }
+
+  fn serialize_u32(self, value: u32) -> Result<Q, Error> {
+    Ok(Q::Long(i64::from(value)))
+  }
+
+  fn serialize_u64(self, value: u64) -> Result<Q, Error> {
+    i64::try_from(value)
+      .map(Q::Long)
+      .map_err(|_| serde::ser::Error::custom(format!("{} does not fit into a q long", value)))
+  }
+
+  fn serialize_f32(self, value: f32) -> Result<Q, Error> {
+    Ok(Q::Real(value))
+  }
+
+  fn serialize_f64(self, value: f64) -> Result<Q, Error> {
+    Ok(Q::Float(value))
+  }
+
+  fn serialize_char(self, value: char) -> Result<Q, Error> {
+    Ok(Q::Char(value))
+  }
+
+  fn serialize_str(self, value: &str) -> Result<Q, Error> {
+    Ok(Q::String(value.to_string()))
+  }
+
+  fn serialize_bytes(self, value: &[u8]) -> Result<Q, Error> {
+    Ok(Q::ByteList(QList::new(value.to_vec())))
+  }
+
+  fn serialize_none(self) -> Result<Q, Error> {
+    Ok(Q::Null)
+  }
+
+  fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Q, Error> {
+    value.serialize(QSerializer)
+  }
+
+  fn serialize_unit(self) -> Result<Q, Error> {
+    Ok(Q::Null)
+  }
+
+  fn serialize_unit_struct(self, _name: &'static str) -> Result<Q, Error> {
+    Ok(Q::Null)
+  }
+
+  fn serialize_unit_variant(
+    self,
+    _name: &'static str,
+    _index: u32,
+    variant: &'static str,
+  ) -> Result<Q, Error> {
+    Ok(Q::Symbol(variant.to_string()))
+  }
+
+  fn serialize_newtype_struct<T: ?Sized + Serialize>(
+    self,
+    _name: &'static str,
+    value: &T,
+  ) -> Result<Q, Error> {
+    value.serialize(QSerializer)
+  }
+
+  fn serialize_newtype_variant<T: ?Sized + Serialize>(
+    self,
+    _name: &'static str,
+    _index: u32,
+    variant: &'static str,
+    value: &T,
+  ) -> Result<Q, Error> {
+    Ok(variant_dictionary(variant, value.serialize(QSerializer)?))
+  }
+
+  fn serialize_seq(self, length: Option<usize>) -> Result<SeqSerializer, Error> {
+    Ok(SeqSerializer {
+      items: Vec::with_capacity(length.unwrap_or(0)),
+    })
+  }
+
+  fn serialize_tuple(self, length: usize) -> Result<SeqSerializer, Error> {
+    self.serialize_seq(Some(length))
+  }
+
+  fn serialize_tuple_struct(
+    self,
+    _name: &'static str,
+    length: usize,
+  ) -> Result<SeqSerializer, Error> {
+    self.serialize_seq(Some(length))
+  }
+
+  fn serialize_tuple_variant(
+    self,
+    _name: &'static str,
+    _index: u32,
+    variant: &'static str,
+    length: usize,
+  ) -> Result<VariantSerializer, Error> {
+    Ok(VariantSerializer {
+      variant,
+      items: Vec::with_capacity(length),
+      keys: Vec::new(),
+    })
+  }
+
+  fn serialize_map(self, length: Option<usize>) -> Result<MapSerializer, Error> {
+    Ok(MapSerializer {
+      keys: Vec::with_capacity(length.unwrap_or(0)),
+      values: Vec::with_capacity(length.unwrap_or(0)),
+    })
+  }
+
+  fn serialize_struct(
+    self,
+    _name: &'static str,
+    length: usize,
+  ) -> Result<StructSerializer, Error> {
+    Ok(StructSerializer {
+      keys: Vec::with_capacity(length),
+      values: Vec::with_capacity(length),
+    })
+  }
+
+  fn serialize_struct_variant(
+    self,
+    _name: &'static str,
+    _index: u32,
+    variant: &'static str,
+    length: usize,
+  ) -> Result<VariantSerializer, Error> {
+    Ok(VariantSerializer {
+      variant,
+      items: Vec::with_capacity(length),
+      keys: Vec::with_capacity(length),
+    })
+  }
+}
+
+impl SerializeSeq for SeqSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+    self.items.push(value.serialize(QSerializer)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    Ok(condense_list(self.items))
+  }
+}
+
+impl SerializeTuple for SeqSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+    SerializeSeq::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    SerializeSeq::end(self)
+  }
+}
+
+impl SerializeTupleStruct for SeqSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+    SerializeSeq::serialize_element(self, value)
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    SerializeSeq::end(self)
+  }
+}
+
+impl SerializeTupleVariant for VariantSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+    self.items.push(value.serialize(QSerializer)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    Ok(variant_dictionary(self.variant, condense_list(self.items)))
+  }
+}
+
+impl SerializeMap for MapSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+    // String keys become symbols, the natural q dictionary key type.
+    self.keys.push(match key.serialize(QSerializer)? {
+      Q::String(text) => Q::Symbol(text),
+      other => other,
+    });
+    Ok(())
+  }
+
+  fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+    self.values.push(value.serialize(QSerializer)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    Ok(Q::Dictionary(QDictionary::new(
+      condense_list(self.keys),
+      condense_list(self.values),
+    )))
+  }
+}
+
+impl SerializeStruct for StructSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_field<T: ?Sized + Serialize>(
+    &mut self,
+    key: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    self.keys.push(key.to_string());
+    self.values.push(value.serialize(QSerializer)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    Ok(Q::Dictionary(QDictionary::new(
+      Q::SymbolList(QList::new(self.keys)),
+      condense_list(self.values),
+    )))
+  }
+}
+
+impl SerializeStructVariant for VariantSerializer {
+  type Ok = Q;
+  type Error = Error;
+
+  fn serialize_field<T: ?Sized + Serialize>(
+    &mut self,
+    key: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    self.keys.push(key.to_string());
+    self.items.push(value.serialize(QSerializer)?);
+    Ok(())
+  }
+
+  fn end(self) -> Result<Q, Error> {
+    let fields = Q::Dictionary(QDictionary::new(
+      Q::SymbolList(QList::new(self.keys)),
+      condense_list(self.items),
+    ));
+    Ok(variant_dictionary(self.variant, fields))
+  }
+}
+
+//%% QDeserializer %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// serde deserializer driven by a [`Q`] object.
+struct QDeserializer {
+  /// Object being deserialized.
+  object: Q,
+}
+
+impl<'de> Deserializer<'de> for QDeserializer {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    match self.object {
+      Q::Bool(value) => visitor.visit_bool(value),
+      Q::Guid(value) => visitor.visit_bytes(&value),
+      Q::Byte(value) => visitor.visit_u8(value),
+      Q::Short(value) => visitor.visit_i16(value),
+      Q::Int(value) => visitor.visit_i32(value),
+      Q::Long(value) => visitor.visit_i64(value),
+      Q::Real(value) => visitor.visit_f32(value),
+      Q::Float(value) => visitor.visit_f64(value),
+      Q::Char(value) => visitor.visit_char(value),
+      Q::Symbol(value) => visitor.visit_string(value),
+      Q::String(value) => visitor.visit_string(value),
+      // Temporal atoms surface their raw q representation.
+      Q::Timestamp(value) | Q::Timespan(value) => visitor.visit_i64(value),
+      Q::Month(value)
+      | Q::Date(value)
+      | Q::Minute(value)
+      | Q::Second(value)
+      | Q::Time(value) => visitor.visit_i32(value),
+      Q::Datetime(value) => visitor.visit_f64(value),
+      Q::Table(table) => visitor.visit_seq(ItemsAccess::new(table_rows(table)?)),
+      Q::Dictionary(dictionary) => {
+        let (keys, values) = dictionary.into_parts();
+        visitor.visit_map(PairsAccess::new(items_of(keys)?, items_of(values)?))
+      }
+      Q::Null => visitor.visit_unit(),
+      list => visitor.visit_seq(ItemsAccess::new(items_of(list)?)),
+    }
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    match self.object {
+      Q::Null => visitor.visit_none(),
+      _ => visitor.visit_some(self),
+    }
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    match self.object {
+      // A bare symbol is a unit variant.
+      Q::Symbol(variant) | Q::String(variant) => visitor.visit_enum(QEnumAccess {
+        variant,
+        value: None,
+      }),
+      // A single-entry dictionary carries the variant payload.
+      Q::Dictionary(dictionary) => {
+        let (keys, values) = dictionary.into_parts();
+        let mut keys = items_of(keys)?;
+        let mut values = items_of(values)?;
+        if keys.len() != 1 || values.len() != 1 {
+          return Err(serde::de::Error::custom(
+            "an enum converts only from a single-entry dictionary",
+          ));
+        }
+        let variant = match keys.remove(0) {
+          Q::Symbol(variant) | Q::String(variant) => variant,
+          other => {
+            return Err(serde::de::Error::custom(format!(
+              "unexpected variant key {:?}",
+              other
+            )));
+          }
+        };
+        visitor.visit_enum(QEnumAccess {
+          variant,
+          value: Some(values.remove(0)),
+        })
+      }
+      other => Err(serde::de::Error::custom(format!(
+        "cannot deserialize an enum from {:?}",
+        other
+      ))),
+    }
+  }
+
+  serde::forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+    identifier ignored_any
+  }
+}
+
+/// Sequence access over the items of a list.
+struct ItemsAccess {
+  /// Remaining items, in order.
+  items: std::vec::IntoIter<Q>,
+}
+
+impl ItemsAccess {
+  /// Wrap the items of a list.
+  fn new(items: Vec<Q>) -> Self {
+    ItemsAccess {
+      items: items.into_iter(),
+    }
+  }
+}
+
+impl<'de> SeqAccess<'de> for ItemsAccess {
+  type Error = Error;
+
+  fn next_element_seed<T: serde::de::DeserializeSeed<'de>>(
+    &mut self,
+    seed: T,
+  ) -> Result<Option<T::Value>, Error> {
+    match self.items.next() {
+      Some(object) => seed.deserialize(QDeserializer { object }).map(Some),
+      None => Ok(None),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.items.len())
+  }
+}
+
+/// Map access over parallel key and value items of a dictionary.
+struct PairsAccess {
+  /// Remaining keys, in order.
+  keys: std::vec::IntoIter<Q>,
+  /// Remaining values, in order.
+  values: std::vec::IntoIter<Q>,
+}
+
+impl PairsAccess {
+  /// Wrap the key and value items of a dictionary.
+  fn new(keys: Vec<Q>, values: Vec<Q>) -> Self {
+    PairsAccess {
+      keys: keys.into_iter(),
+      values: values.into_iter(),
+    }
+  }
+}
+
+impl<'de> MapAccess<'de> for PairsAccess {
+  type Error = Error;
+
+  fn next_key_seed<K: serde::de::DeserializeSeed<'de>>(
+    &mut self,
+    seed: K,
+  ) -> Result<Option<K::Value>, Error> {
+    match self.keys.next() {
+      Some(object) => seed.deserialize(QDeserializer { object }).map(Some),
+      None => Ok(None),
+    }
+  }
+
+  fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(
+    &mut self,
+    seed: V,
+  ) -> Result<V::Value, Error> {
+    match self.values.next() {
+      Some(object) => seed.deserialize(QDeserializer { object }),
+      None => Err(serde::de::Error::custom(
+        "dictionary values exhausted before keys",
+      )),
+    }
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.keys.len())
+  }
+}
+
+/// Enum access over a unit variant symbol or a single-entry dictionary.
+struct QEnumAccess {
+  /// Name of the variant.
+  variant: String,
+  /// Payload of the variant, absent for unit variants.
+  value: Option<Q>,
+}
+
+impl<'de> EnumAccess<'de> for QEnumAccess {
+  type Error = Error;
+  type Variant = QVariantAccess;
+
+  fn variant_seed<V: serde::de::DeserializeSeed<'de>>(
+    self,
+    seed: V,
+  ) -> Result<(V::Value, QVariantAccess), Error> {
+    let variant = seed.deserialize(self.variant.into_deserializer())?;
+    Ok((variant, QVariantAccess { value: self.value }))
+  }
+}
+
+/// Variant access over the payload of an enum.
+struct QVariantAccess {
+  /// Payload of the variant, absent for unit variants.
+  value: Option<Q>,
+}
+
+impl<'de> VariantAccess<'de> for QVariantAccess {
+  type Error = Error;
+
+  fn unit_variant(self) -> Result<(), Error> {
+    match self.value {
+      None | Some(Q::Null) => Ok(()),
+      Some(other) => Err(serde::de::Error::custom(format!(
+        "unexpected payload {:?} for a unit variant",
+        other
+      ))),
+    }
+  }
+
+  fn newtype_variant_seed<T: serde::de::DeserializeSeed<'de>>(
+    self,
+    seed: T,
+  ) -> Result<T::Value, Error> {
+    match self.value {
+      Some(object) => seed.deserialize(QDeserializer { object }),
+      None => Err(serde::de::Error::custom("missing payload for a variant")),
+    }
+  }
+
+  fn tuple_variant<V: Visitor<'de>>(self, _length: usize, visitor: V) -> Result<V::Value, Error> {
+    match self.value {
+      Some(object) => QDeserializer { object }.deserialize_any(visitor),
+      None => Err(serde::de::Error::custom("missing payload for a variant")),
+    }
+  }
+
+  fn struct_variant<V: Visitor<'de>>(
+    self,
+    _fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    match self.value {
+      Some(object) => QDeserializer { object }.deserialize_any(visitor),
+      None => Err(serde::de::Error::custom("missing payload for a variant")),
+    }
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Convert any `Serialize` value into a [`Q`] object.
+/// # Parameters
+/// - `value`: Value to convert, e.g. a `#[derive(Serialize)]` struct.
+pub fn to_q<T: ?Sized + Serialize>(value: &T) -> io::Result<Q> {
+  value.serialize(QSerializer).map_err(io::Error::from)
+}
+
+/// Convert a [`Q`] object into any `Deserialize` type, failing with an
+///  error of kind `InvalidData` when the object does not fit the type.
+/// # Parameters
+/// - `object`: Object to convert, e.g. a query response.
+pub fn from_q<T: DeserializeOwned>(object: Q) -> io::Result<T> {
+  T::deserialize(QDeserializer { object }).map_err(io::Error::from)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Build the single-entry dictionary carrying an enum variant payload.
+fn variant_dictionary(variant: &str, value: Q) -> Q {
+  Q::Dictionary(QDictionary::new(
+    Q::SymbolList(QList::new(vec![variant.to_string()])),
+    Q::MixedList(vec![value]),
+  ))
+}
+
+/// Condense a mixed list into the matching simple list when all items are
+///  atoms of the same type, mirroring how q itself types homogeneous data.
+fn condense_list(items: Vec<Q>) -> Q {
+  match items.first() {
+    Some(Q::Bool(_)) if items.iter().all(|item| matches!(item, Q::Bool(_))) => {
+      Q::BoolList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Bool(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Byte(_)) if items.iter().all(|item| matches!(item, Q::Byte(_))) => {
+      Q::ByteList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Byte(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Short(_)) if items.iter().all(|item| matches!(item, Q::Short(_))) => {
+      Q::ShortList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Short(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Int(_)) if items.iter().all(|item| matches!(item, Q::Int(_))) => {
+      Q::IntList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Int(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Long(_)) if items.iter().all(|item| matches!(item, Q::Long(_))) => {
+      Q::LongList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Long(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Real(_)) if items.iter().all(|item| matches!(item, Q::Real(_))) => {
+      Q::RealList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Real(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Float(_)) if items.iter().all(|item| matches!(item, Q::Float(_))) => {
+      Q::FloatList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Float(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    Some(Q::Char(_)) if items.iter().all(|item| matches!(item, Q::Char(_))) => Q::String(
+      items
+        .into_iter()
+        .map(|item| match item {
+          Q::Char(value) => value,
+          _ => unreachable!("checked above"),
+        })
+        .collect(),
+    ),
+    Some(Q::Symbol(_)) if items.iter().all(|item| matches!(item, Q::Symbol(_))) => {
+      Q::SymbolList(QList::new(
+        items
+          .into_iter()
+          .map(|item| match item {
+            Q::Symbol(value) => value,
+            _ => unreachable!("checked above"),
+          })
+          .collect(),
+      ))
+    }
+    _ => Q::MixedList(items),
+  }
+}
+
+/// Break a list of any kind into its items.
+fn items_of(object: Q) -> Result<Vec<Q>, Error> {
+  crate::convert::q_list_items(object).map_err(|error| Error(error.to_string()))
+}
+
+/// Break a table into one dictionary per row, sharing the column names.
+fn table_rows(table: crate::qtype::QTable) -> Result<Vec<Q>, Error> {
+  let (columns, values) = table.into_parts();
+  let mut column_items = Vec::with_capacity(values.len());
+  for value in values {
+    column_items.push(items_of(value)?);
+  }
+  let length = column_items.first().map_or(0, Vec::len);
+  if column_items.iter().any(|items| items.len() != length) {
+    return Err(Error("ragged columns in a table".to_string()));
+  }
+  let mut rows = Vec::with_capacity(length);
+  for index in 0..length {
+    let row_values: Vec<Q> = column_items
+      .iter_mut()
+      .map(|items| std::mem::replace(&mut items[index], Q::Null))
+      .collect();
+    rows.push(Q::Dictionary(QDictionary::new(
+      Q::SymbolList(QList::new(columns.clone())),
+      Q::MixedList(row_values),
+    )));
+  }
+  Ok(rows)
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::qtype::QTable;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+  struct Trade {
+    sym: String,
+    price: f64,
+    sizes: Vec<i64>,
+    venue: Option<String>,
+  }
+
+  #[test]
+  fn structs_round_trip_through_dictionaries() {
+    let trade = Trade {
+      sym: "abc".to_string(),
+      price: 102.5,
+      sizes: vec![100, 200],
+      venue: None,
+    };
+    let object = to_q(&trade).unwrap();
+    assert_eq!(
+      object,
+      Q::Dictionary(QDictionary::new(
+        Q::SymbolList(QList::new(vec![
+          "sym".to_string(),
+          "price".to_string(),
+          "sizes".to_string(),
+          "venue".to_string(),
+        ])),
+        Q::MixedList(vec![
+          Q::String("abc".to_string()),
+          Q::Float(102.5),
+          Q::LongList(QList::new(vec![100, 200])),
+          Q::Null,
+        ]),
+      ))
+    );
+    assert_eq!(from_q::<Trade>(object).unwrap(), trade);
+  }
+
+  #[derive(Serialize, Deserialize, Debug, PartialEq)]
+  enum Instruction {
+    Cancel,
+    Limit { price: f64, size: i64 },
+  }
+
+  #[test]
+  fn enums_and_tables_deserialize() {
+    let cancel = to_q(&Instruction::Cancel).unwrap();
+    assert_eq!(cancel, Q::Symbol("Cancel".to_string()));
+    assert_eq!(from_q::<Instruction>(cancel).unwrap(), Instruction::Cancel);
+    let limit = to_q(&Instruction::Limit {
+      price: 1.5,
+      size: 10,
+    })
+    .unwrap();
+    assert_eq!(
+      from_q::<Instruction>(limit).unwrap(),
+      Instruction::Limit {
+        price: 1.5,
+        size: 10,
+      }
+    );
+
+    // A table deserializes as a sequence of row structs.
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row {
+      sym: String,
+      price: f64,
+    }
+    let table = Q::Table(
+      QTable::new(
+        vec!["sym".to_string(), "price".to_string()],
+        vec![
+          Q::SymbolList(QList::new(vec!["a".to_string(), "b".to_string()])),
+          Q::FloatList(QList::new(vec![1.0, 2.0])),
+        ],
+      )
+      .unwrap(),
+    );
+    assert_eq!(
+      from_q::<Vec<Row>>(table).unwrap(),
+      vec![
+        Row {
+          sym: "a".to_string(),
+          price: 1.0,
+        },
+        Row {
+          sym: "b".to_string(),
+          price: 2.0,
+        },
+      ]
+    );
+  }
+}
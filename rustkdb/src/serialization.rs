@@ -0,0 +1,376 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Serialization of [`Q`] objects into the kdb+ IPC wire format.
+//!
+//! Messages are encoded little endian unless told otherwise; the endianness
+//! flag in the message header tells the remote side how to decode them.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use crate::qtype::*;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Global Variables                   //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Message type of an asynchronous message.
+pub(crate) const MSG_TYPE_ASYNC: u8 = 0;
+/// Message type of a synchronous message.
+pub(crate) const MSG_TYPE_SYNC: u8 = 1;
+
+/// Message type of a response message.
+pub(crate) const MSG_TYPE_RESPONSE: u8 = 2;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Byte order of outgoing IPC messages. Incoming messages declare their own
+///  byte order in the message header and both are always accepted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endian {
+  /// Little endian, the byte order of every common kdb+ platform.
+  #[default]
+  Little,
+  /// Big endian.
+  Big,
+}
+
+impl Endian {
+  /// Endianness flag of the message header: 1 for little endian.
+  fn to_flag(self) -> u8 {
+    match self {
+      Endian::Little => 1,
+      Endian::Big => 0,
+    }
+  }
+}
+
+/// Numeric scalar written to the wire in a chosen byte order.
+trait WireScalar {
+  /// Append the bytes of the scalar in the given byte order.
+  fn write(&self, endian: Endian, out: &mut Vec<u8>);
+}
+
+macro_rules! impl_wire_scalar {
+  ($($type:ty),*) => {$(
+    impl WireScalar for $type {
+      fn write(&self, endian: Endian, out: &mut Vec<u8>) {
+        match endian {
+          Endian::Little => out.extend_from_slice(&self.to_le_bytes()),
+          Endian::Big => out.extend_from_slice(&self.to_be_bytes()),
+        }
+      }
+    }
+  )*};
+}
+
+impl_wire_scalar!(i16, i32, i64, u32, f32, f64);
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Serialize a simple list, i.e. type code, attribute, length and raw items.
+fn serialize_list<T, F>(
+  type_code: i8,
+  list: &QList<T>,
+  out: &mut Vec<u8>,
+  endian: Endian,
+  write_item: F,
+) where
+  F: Fn(&T, &mut Vec<u8>),
+{
+  out.push(type_code as u8);
+  out.push(list.attribute().to_byte());
+  (list.len() as u32).write(endian, out);
+  for item in list.data() {
+    write_item(item, out);
+  }
+}
+
+/// Serialize a symbol, i.e. its bytes followed by a null terminator.
+fn serialize_symbol(symbol: &str, out: &mut Vec<u8>) {
+  out.extend_from_slice(symbol.as_bytes());
+  out.push(0);
+}
+
+/// Serialize the body of a q object in the given byte order.
+fn serialize_q_endian(q: &Q, out: &mut Vec<u8>, endian: Endian) {
+  match q {
+    Q::Bool(value) => {
+      out.push(-1_i8 as u8);
+      out.push(*value as u8);
+    }
+    Q::Guid(value) => {
+      out.push(-2_i8 as u8);
+      out.extend_from_slice(value);
+    }
+    Q::Byte(value) => {
+      out.push(-4_i8 as u8);
+      out.push(*value);
+    }
+    Q::Short(value) => {
+      out.push(-5_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Int(value) => {
+      out.push(-6_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Long(value) => {
+      out.push(-7_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Real(value) => {
+      out.push(-8_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Float(value) => {
+      out.push(-9_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Char(value) => {
+      out.push(-10_i8 as u8);
+      out.push(*value as u8);
+    }
+    Q::Symbol(value) => {
+      out.push(-11_i8 as u8);
+      serialize_symbol(value, out);
+    }
+    Q::Timestamp(value) => {
+      out.push(-12_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Month(value) => {
+      out.push(-13_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Date(value) => {
+      out.push(-14_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Datetime(value) => {
+      out.push(-15_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Timespan(value) => {
+      out.push(-16_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Minute(value) => {
+      out.push(-17_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Second(value) => {
+      out.push(-18_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::Time(value) => {
+      out.push(-19_i8 as u8);
+      value.write(endian, out);
+    }
+    Q::BoolList(list) => serialize_list(1, list, out, endian, |item, out| out.push(*item as u8)),
+    Q::GuidList(list) => {
+      serialize_list(2, list, out, endian, |item, out| out.extend_from_slice(item))
+    }
+    Q::ByteList(list) => serialize_list(4, list, out, endian, |item, out| out.push(*item)),
+    Q::ShortList(list) => {
+      serialize_list(5, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::IntList(list) => {
+      serialize_list(6, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::LongList(list) => {
+      serialize_list(7, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::RealList(list) => {
+      serialize_list(8, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::FloatList(list) => {
+      serialize_list(9, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::String(value) => {
+      out.push(10);
+      out.push(0);
+      (value.len() as u32).write(endian, out);
+      out.extend_from_slice(value.as_bytes());
+    }
+    Q::SymbolList(list) => {
+      serialize_list(11, list, out, endian, |item, out| serialize_symbol(item, out));
+    }
+    Q::TimestampList(list) => {
+      serialize_list(12, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::MonthList(list) => {
+      serialize_list(13, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::DateList(list) => {
+      serialize_list(14, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::DatetimeList(list) => {
+      serialize_list(15, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::TimespanList(list) => {
+      serialize_list(16, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::MinuteList(list) => {
+      serialize_list(17, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::SecondList(list) => {
+      serialize_list(18, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::TimeList(list) => {
+      serialize_list(19, list, out, endian, |item, out| item.write(endian, out));
+    }
+    Q::Enum(enumeration) => {
+      serialize_list(
+        enumeration.type_code(),
+        enumeration.indices(),
+        out,
+        endian,
+        |item, out| item.write(endian, out),
+      );
+    }
+    Q::MixedList(items) => {
+      out.push(Q_MIXED_LIST as u8);
+      out.push(0);
+      (items.len() as u32).write(endian, out);
+      for item in items {
+        serialize_q_endian(item, out, endian);
+      }
+    }
+    Q::Table(table) => {
+      out.push(Q_TABLE as u8);
+      out.push(0);
+      out.push(Q_DICTIONARY as u8);
+      serialize_q_endian(
+        &Q::SymbolList(QList::new(table.columns().to_vec())),
+        out,
+        endian,
+      );
+      serialize_q_endian(&Q::MixedList(table.values().to_vec()), out, endian);
+    }
+    Q::Dictionary(dictionary) => {
+      out.push(if dictionary.is_sorted() {
+        Q_SORTED_DICTIONARY as u8
+      } else {
+        Q_DICTIONARY as u8
+      });
+      serialize_q_endian(dictionary.keys(), out, endian);
+      serialize_q_endian(dictionary.values(), out, endian);
+    }
+    Q::Function(function) => match function {
+      QFunction::Lambda { context, body } => {
+        out.push(Q_LAMBDA as u8);
+        serialize_symbol(context, out);
+        out.push(10);
+        out.push(0);
+        (body.len() as u32).write(endian, out);
+        out.extend_from_slice(body.as_bytes());
+      }
+      QFunction::UnaryPrimitive(code) => {
+        out.push(Q_GENERAL_NULL as u8);
+        out.push(*code);
+      }
+      QFunction::Operator(code) => {
+        out.push(Q_OPERATOR as u8);
+        out.push(*code);
+      }
+      QFunction::Iterator(code) => {
+        out.push(Q_ITERATOR as u8);
+        out.push(*code);
+      }
+      QFunction::Projection(items) => {
+        out.push(Q_PROJECTION as u8);
+        (items.len() as u32).write(endian, out);
+        for item in items {
+          serialize_q_endian(item, out, endian);
+        }
+      }
+      QFunction::Composition(items) => {
+        out.push(Q_COMPOSITION as u8);
+        (items.len() as u32).write(endian, out);
+        for item in items {
+          serialize_q_endian(item, out, endian);
+        }
+      }
+      QFunction::Derived(type_code, inner) => {
+        out.push(*type_code as u8);
+        serialize_q_endian(inner, out, endian);
+      }
+    },
+    Q::Raw { type_code, bytes } => {
+      out.push(*type_code as u8);
+      out.extend_from_slice(bytes);
+    }
+    Q::Error(message) => {
+      out.push(Q_ERROR as u8);
+      serialize_symbol(message, out);
+    }
+    Q::Null => {
+      out.push(Q_GENERAL_NULL as u8);
+      out.push(0);
+    }
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Serialize the body of a q object little endian, i.e. everything but the
+///  message header.
+pub(crate) fn serialize_q(q: &Q, out: &mut Vec<u8>) {
+  serialize_q_endian(q, out, Endian::Little);
+}
+
+/// Serialize a q object into a complete IPC message including the 8 byte
+///  header, in the given byte order. `message_type` is one of
+///  `MSG_TYPE_ASYNC`, `MSG_TYPE_SYNC` and `MSG_TYPE_RESPONSE`.
+pub(crate) fn serialize_message_endian(q: &Q, message_type: u8, endian: Endian) -> Vec<u8> {
+  let mut body = Vec::new();
+  serialize_q_endian(q, &mut body, endian);
+  let mut message = Vec::with_capacity(8 + body.len());
+  // Endianness flag, message type, no compression, reserved byte.
+  message.extend_from_slice(&[endian.to_flag(), message_type, 0, 0]);
+  ((8 + body.len()) as u32).write(endian, &mut message);
+  message.extend_from_slice(&body);
+  message
+}
+
+/// Serialize a q object into a complete little endian IPC message.
+pub(crate) fn serialize_message(q: &Q, message_type: u8) -> Vec<u8> {
+  serialize_message_endian(q, message_type, Endian::Little)
+}
+
+/// Serialize a plain string query into a complete IPC message in the given
+///  byte order. The query is sent as a char list.
+pub(crate) fn serialize_string_query_endian(
+  query: &str,
+  message_type: u8,
+  endian: Endian,
+) -> Vec<u8> {
+  serialize_message_endian(&Q::String(query.to_string()), message_type, endian)
+}
+
+/// Serialize a plain string query into a complete little endian IPC message.
+pub(crate) fn serialize_string_query(query: &str, message_type: u8) -> Vec<u8> {
+  serialize_string_query_endian(query, message_type, Endian::Little)
+}
+
+/// Serialize a q error (type -128) into a complete response message, the
+///  wire form of `'message` signalled by a q server.
+pub(crate) fn serialize_error_response(message: &str) -> Vec<u8> {
+  let mut full = Vec::with_capacity(8 + 2 + message.len());
+  full.extend_from_slice(&[1, MSG_TYPE_RESPONSE, 0, 0]);
+  full.extend_from_slice(&((8 + 2 + message.len()) as u32).to_le_bytes());
+  full.push(0x80);
+  full.extend_from_slice(message.as_bytes());
+  full.push(0);
+  full
+}
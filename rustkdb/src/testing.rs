@@ -0,0 +1,251 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Embedded mock q server for integration tests.
+//!
+//! [`MockServer`] imitates a q process over the IPC protocol — canned
+//! responses keyed by query string, scripted q errors and delayed replies —
+//! so code built on this crate can be tested without a licensed q binary.
+//! The server listens on a real TCP port, so the code under test connects
+//! with the ordinary [`connect`](crate::connection::connect) path including
+//! the handshake.
+//!
+//! # Example
+//! ```
+//! use rustkdb::connection::connect;
+//! use rustkdb::qtype::Q;
+//! use rustkdb::testing::MockServer;
+//!
+//! # #[tokio::main(flavor = "current_thread")] async fn main() -> std::io::Result<()> {
+//! let server = MockServer::builder()
+//!   .respond("6*7", Q::Long(42))
+//!   .fail("boom", "type")
+//!   .start()
+//!   .await?;
+//! let mut handle = connect("127.0.0.1", server.port(), "kdbuser:pass", 1000, 0).await?;
+//! assert_eq!(handle.send_string_query("6*7").await?, Q::Long(42));
+//! assert!(handle.send_string_query("boom").await.is_err());
+//! # Ok(())}
+//! ```
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::connection::MessageType;
+use crate::listen::Listener;
+use crate::qtype::Q;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% MockServer %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// What the mock sends back for one canned query.
+enum CannedAction {
+  /// Reply with a q object.
+  Value(Q),
+  /// Reply with a q error, i.e. `'message`.
+  Error(String),
+}
+
+/// One scripted reply of the mock server.
+struct CannedReply {
+  /// The reply itself.
+  action: CannedAction,
+  /// Wait this long before replying, imitating a slow calculation.
+  delay: Option<Duration>,
+}
+
+/// Builder of a [`MockServer`], obtained from [`MockServer::builder`].
+#[derive(Default)]
+pub struct MockServerBuilder {
+  /// Scripted replies keyed by query string.
+  canned: HashMap<String, CannedReply>,
+}
+
+impl MockServerBuilder {
+  /// Script a reply for a query.
+  /// # Parameters
+  /// - `query`: Query string to match, e.g. `"6*7"`.
+  /// - `response`: Object sent back as the response.
+  pub fn respond(mut self, query: &str, response: Q) -> Self {
+    self.canned.insert(
+      query.to_string(),
+      CannedReply {
+        action: CannedAction::Value(response),
+        delay: None,
+      },
+    );
+    self
+  }
+
+  /// Script a reply which arrives only after the given delay, imitating a
+  ///  slow calculation or a congested server.
+  pub fn respond_with_delay(mut self, query: &str, response: Q, delay: Duration) -> Self {
+    self.canned.insert(
+      query.to_string(),
+      CannedReply {
+        action: CannedAction::Value(response),
+        delay: Some(delay),
+      },
+    );
+    self
+  }
+
+  /// Script a q error for a query, observed by the client exactly like an
+  ///  error signalled by a real q server.
+  /// # Parameters
+  /// - `query`: Query string to match.
+  /// - `error`: Error message without the leading quote, e.g. `"type"`.
+  pub fn fail(mut self, query: &str, error: &str) -> Self {
+    self.canned.insert(
+      query.to_string(),
+      CannedReply {
+        action: CannedAction::Error(error.to_string()),
+        delay: None,
+      },
+    );
+    self
+  }
+
+  /// Bind a free local port and start serving connections. Any credential
+  ///  is accepted. Queries without a scripted reply are answered with the
+  ///  q error `'nyi`.
+  pub async fn start(self) -> io::Result<MockServer> {
+    let listener = Listener::bind("127.0.0.1", 0).await?;
+    let port = listener.local_port()?;
+    let canned = Arc::new(self.canned);
+    let task = tokio::spawn(async move {
+      let _ = listener
+        .serve(move |mut handle| {
+          let canned = Arc::clone(&canned);
+          async move {
+            while let Ok((message_type, query)) = handle.receive_message().await {
+              if message_type != MessageType::Sync {
+                continue;
+              }
+              let result = match query_key(&query).and_then(|key| canned.get(&key)) {
+                Some(reply) => {
+                  if let Some(delay) = reply.delay {
+                    tokio::time::sleep(delay).await;
+                  }
+                  match &reply.action {
+                    CannedAction::Value(response) => handle.send_response(response.clone()).await,
+                    CannedAction::Error(error) => handle.send_error_response(error).await,
+                  }
+                }
+                None => handle.send_error_response("nyi").await,
+              };
+              if result.is_err() {
+                break;
+              }
+            }
+          }
+        })
+        .await;
+    });
+    Ok(MockServer { port, task })
+  }
+}
+
+/// Running mock q server. The server stops when the value is dropped.
+pub struct MockServer {
+  /// Port the server listens on.
+  port: u16,
+  /// Background accept loop.
+  task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+  /// Start building a mock server.
+  pub fn builder() -> MockServerBuilder {
+    MockServerBuilder::default()
+  }
+
+  /// Port the server listens on, to be passed to
+  ///  [`connect`](crate::connection::connect) by the code under test.
+  pub fn port(&self) -> u16 {
+    self.port
+  }
+}
+
+impl Drop for MockServer {
+  fn drop(&mut self) {
+    self.task.abort();
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Key under which an incoming query is looked up: the text of string
+///  queries, single characters and symbols. Other objects never match a
+///  canned reply.
+fn query_key(query: &Q) -> Option<String> {
+  match query {
+    Q::String(text) => Some(text.clone()),
+    Q::Char(character) => Some(character.to_string()),
+    Q::Symbol(symbol) => Some(symbol.clone()),
+    _ => None,
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::connection::connect;
+  use std::time::Instant;
+
+  #[tokio::test]
+  async fn canned_and_scripted_error_replies() {
+    let server = MockServer::builder()
+      .respond("6*7", Q::Long(42))
+      .fail("boom", "type")
+      .start()
+      .await
+      .unwrap();
+    let mut handle = connect("127.0.0.1", server.port(), "kdbuser:pass", 1000, 0)
+      .await
+      .unwrap();
+    assert_eq!(handle.send_string_query("6*7").await.unwrap(), Q::Long(42));
+    let error = handle
+      .send_string_query("boom")
+      .await
+      .expect_err("the scripted error must surface");
+    assert!(error.to_string().contains("type"));
+    // Queries without a scripted reply fail with 'nyi instead of hanging.
+    let error = handle
+      .send_string_query("undefined")
+      .await
+      .expect_err("unscripted queries must fail");
+    assert!(error.to_string().contains("nyi"));
+  }
+
+  #[tokio::test]
+  async fn delayed_replies_arrive_late() {
+    let server = MockServer::builder()
+      .respond_with_delay("slow", Q::Long(1), Duration::from_millis(50))
+      .start()
+      .await
+      .unwrap();
+    let mut handle = connect("127.0.0.1", server.port(), "kdbuser:pass", 1000, 0)
+      .await
+      .unwrap();
+    let started = Instant::now();
+    assert_eq!(handle.send_string_query("slow").await.unwrap(), Q::Long(1));
+    assert!(started.elapsed() >= Duration::from_millis(50));
+  }
+}
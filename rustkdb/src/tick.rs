@@ -0,0 +1,724 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Tickerplant helpers: subscribing to and publishing tables.
+//!
+//! [`subscribe`] speaks the conventional `.u.sub`/`upd` protocol of the
+//! kdb+ tick architecture, turning a connected [`Handle`] into a typed
+//! stream of table updates so subscriber code never touches the raw mixed
+//! lists on the wire. [`publish`] and [`Publisher`] build the matching
+//! `.u.upd` calls for feedhandlers, the latter batching several ticks per
+//! table into one call. [`TplogReader`] and [`TplogWriter`] decode and
+//! write tickerplant log files for recovery tooling.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::io;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::connection::{is_disconnection, Handle, MessageType};
+use crate::deserialization::deserialize_q_prefix;
+use crate::qtype::{Q, QList, QTable};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% TableUpdates %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Stream of `(table name, rows)` updates, obtained from [`subscribe`].
+///
+/// Implements [`futures_core::Stream`] with
+///  `Item = io::Result<(String, QTable)>`. Snapshot data returned by
+///  `.u.sub` is yielded first, then every incoming `upd` call in arrival
+///  order. The stream ends once the tickerplant closes the connection; the
+///  terminal disconnection error is yielded first.
+pub struct TableUpdates {
+  /// Updates forwarded by the background read task.
+  receiver: tokio::sync::mpsc::UnboundedReceiver<io::Result<(String, QTable)>>,
+  /// Background read task.
+  task: tokio::task::JoinHandle<()>,
+}
+
+impl futures_core::Stream for TableUpdates {
+  type Item = io::Result<(String, QTable)>;
+
+  fn poll_next(
+    mut self: std::pin::Pin<&mut Self>,
+    context: &mut std::task::Context<'_>,
+  ) -> std::task::Poll<Option<Self::Item>> {
+    self.receiver.poll_recv(context)
+  }
+}
+
+impl Drop for TableUpdates {
+  fn drop(&mut self) {
+    self.task.abort();
+  }
+}
+
+//%% Publisher %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Batching publisher for feedhandlers.
+///
+/// Ticks are buffered per table with their columns appended element-wise;
+///  once a table has accumulated `batch_size` ticks a single combined
+///  `.u.upd` call goes out. Atom columns of single-row ticks are promoted
+///  to vectors when batched, which q's `upd` accepts either way.
+/// # Example
+/// ```no_run
+/// use rustkdb::connection::connect;
+/// use rustkdb::qtype::Q;
+/// use rustkdb::tick::Publisher;
+///
+/// # async fn doc() -> std::io::Result<()> {
+/// let mut handle = connect("localhost", 5010, "kdbuser:pass", 200, 0).await?;
+/// let mut publisher = Publisher::new(100);
+/// let tick = Q::MixedList(vec![Q::Symbol("FDP".to_string()), Q::Float(103.2)]);
+/// publisher.publish(&mut handle, "trade", tick).await?;
+/// // At the end of the feed, push out any incomplete batch.
+/// publisher.flush(&mut handle).await?;
+/// # Ok(())}
+/// ```
+pub struct Publisher {
+  /// Number of ticks per table triggering a combined `.u.upd` call.
+  batch_size: usize,
+  /// Buffered data per table, in first-publish order.
+  pending: Vec<(String, Q, usize)>,
+}
+
+impl Publisher {
+  /// Construct a publisher sending one `.u.upd` call per `batch_size`
+  ///  ticks of a table. A batch size of 1 (or 0) publishes immediately.
+  pub fn new(batch_size: usize) -> Self {
+    Publisher {
+      batch_size,
+      pending: Vec::new(),
+    }
+  }
+
+  /// Buffer one tick, publishing the table once its batch is full.
+  /// # Parameters
+  /// - `handle`: Handle to the tickerplant.
+  /// - `table`: Table the tick belongs to.
+  /// - `data`: Row or columns of the tick, as passed to `.u.upd`.
+  pub async fn publish(&mut self, handle: &mut Handle, table: &str, data: Q) -> io::Result<()> {
+    if self.batch_size <= 1 {
+      return publish(handle, table, data).await;
+    }
+    let data = atoms_to_columns(data);
+    match self.pending.iter_mut().find(|(name, _, _)| name == table) {
+      Some((_, merged, ticks)) => {
+        append_column(merged, data)?;
+        *ticks += 1;
+      }
+      None => self.pending.push((table.to_string(), data, 1)),
+    }
+    let full = self
+      .pending
+      .iter()
+      .position(|(name, _, ticks)| name == table && *ticks >= self.batch_size);
+    if let Some(index) = full {
+      let (table, data, _) = self.pending.remove(index);
+      publish(handle, &table, data).await?;
+    }
+    Ok(())
+  }
+
+  /// Publish every incomplete batch, in first-publish order.
+  pub async fn flush(&mut self, handle: &mut Handle) -> io::Result<()> {
+    for (table, data, _) in self.pending.drain(..) {
+      publish(handle, &table, data).await?;
+    }
+    Ok(())
+  }
+
+  /// Number of tables with a buffered, incomplete batch.
+  pub fn pending_tables(&self) -> usize {
+    self.pending.len()
+  }
+}
+
+//%% TplogReader %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Reader over a kdb+ tickerplant log file (tplog).
+///
+/// A tplog is the serialized list-of-messages q appends to on disk and
+///  replays with `-11!`. The reader iterates over the decoded messages —
+///  each usually a ``(`upd; `table; data)`` call — and can re-send them to
+///  a process with [`replay_into`](TplogReader::replay_into), the building
+///  block of RDB recovery tooling.
+/// # Example
+/// ```no_run
+/// use rustkdb::tick::TplogReader;
+///
+/// # async fn doc() -> std::io::Result<()> {
+/// for message in TplogReader::open("/logs/tick2024.01.15").await? {
+///   println!("{:?}", message?);
+/// }
+/// # Ok(())}
+/// ```
+pub struct TplogReader {
+  /// Contents of the log file.
+  bytes: Vec<u8>,
+  /// Current read position.
+  position: usize,
+}
+
+impl TplogReader {
+  /// Open a tickerplant log file and check its header.
+  pub async fn open(path: &str) -> io::Result<TplogReader> {
+    let bytes = tokio::fs::read(path).await?;
+    if bytes.len() < 8 || bytes[0] != 0xff || bytes[1] != 0x01 {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} is not a kdb+ log file", path),
+      ));
+    }
+    Ok(TplogReader { bytes, position: 8 })
+  }
+
+  /// Re-send every remaining message asynchronously to the given process,
+  ///  mirroring what `-11!` does locally. Returns the number of messages
+  ///  replayed.
+  pub async fn replay_into(mut self, handle: &mut Handle) -> io::Result<u64> {
+    let mut replayed = 0;
+    for message in self.by_ref() {
+      handle.send_query_async(message?).await?;
+      replayed += 1;
+    }
+    Ok(replayed)
+  }
+}
+
+impl Iterator for TplogReader {
+  type Item = io::Result<Q>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.position >= self.bytes.len() {
+      return None;
+    }
+    match deserialize_q_prefix(&self.bytes[self.position..], true) {
+      Ok((message, consumed)) => {
+        self.position += consumed;
+        Some(Ok(message))
+      }
+      // A torn tail — e.g. from a crashed writer — ends the iteration
+      // after surfacing the error once.
+      Err(error) => {
+        self.position = self.bytes.len();
+        Some(Err(error))
+      }
+    }
+  }
+}
+
+//%% TplogWriter %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Appending writer of a kdb+ tickerplant log file (tplog).
+///
+/// Messages are serialized exactly as q appends them, so the resulting
+///  file replays with `-11!` and reads back with [`TplogReader`]. Each
+///  message goes out in a single write, so a crash can at worst leave a
+///  torn message at the tail — which both `-11!` and the reader tolerate.
+/// # Example
+/// ```no_run
+/// use rustkdb::qtype::{Q, QList};
+/// use rustkdb::tick::TplogWriter;
+///
+/// # async fn doc() -> std::io::Result<()> {
+/// let mut log = TplogWriter::open("/logs/tick2024.01.15").await?;
+/// let columns = Q::MixedList(vec![
+///   Q::SymbolList(QList::new(vec!["FDP".to_string()])),
+///   Q::FloatList(QList::new(vec![103.2])),
+/// ]);
+/// log.append_upd("trade", columns).await?;
+/// log.sync().await
+/// # }
+/// ```
+pub struct TplogWriter {
+  /// The log file, positioned at its end.
+  file: tokio::fs::File,
+}
+
+impl TplogWriter {
+  /// Open a log file for appending, creating it — including the log
+  ///  header — when it does not exist yet.
+  pub async fn open(path: &str) -> io::Result<TplogWriter> {
+    let mut file = tokio::fs::OpenOptions::new()
+      .append(true)
+      .create(true)
+      .open(path)
+      .await?;
+    if file.metadata().await?.len() == 0 {
+      file.write_all(&[0xff, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+    }
+    Ok(TplogWriter { file })
+  }
+
+  /// Append one message, usually a ``(`upd; `table; data)`` call.
+  pub async fn append(&mut self, message: &Q) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    crate::serialization::serialize_q(message, &mut bytes);
+    self.file.write_all(&bytes).await
+  }
+
+  /// Append a ``(`upd; `table; data)`` call built from its parts.
+  pub async fn append_upd(&mut self, table: &str, data: Q) -> io::Result<()> {
+    self
+      .append(&Q::MixedList(vec![
+        Q::Symbol("upd".to_string()),
+        Q::Symbol(table.to_string()),
+        data,
+      ]))
+      .await
+  }
+
+  /// Flush appended messages to disk, surviving a host crash.
+  pub async fn sync(&mut self) -> io::Result<()> {
+    self.file.sync_data().await
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Publish one tick to a tickerplant, i.e. send the canonical
+///  ``(.u.upd; `table; data)`` call asynchronously.
+/// # Parameters
+/// - `handle`: Handle to the tickerplant.
+/// - `table`: Table to publish to.
+/// - `data`: Row or columns of the tick, as passed to `.u.upd`.
+pub async fn publish(handle: &mut Handle, table: &str, data: Q) -> io::Result<()> {
+  handle
+    .send_query_async(Q::MixedList(vec![
+      Q::Symbol(".u.upd".to_string()),
+      Q::Symbol(table.to_string()),
+      data,
+    ]))
+    .await
+}
+
+/// Subscribe to tables published by a tickerplant.
+///
+/// Sends `(.u.sub; table; syms)` synchronously for every table and turns
+///  the handle into a stream of `(table name, rows)` pairs. The snapshot
+///  contained in each `.u.sub` reply — non-empty for keyed plants such as
+///  `.u.rep`-style chained tickerplants — is yielded before live updates.
+/// # Parameters
+/// - `handle`: Connected handle, consumed by the subscription.
+/// - `tables`: Tables to subscribe to.
+/// - `syms`: Symbols to filter on; empty subscribes to all symbols.
+/// # Example
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use rustkdb::connection::connect;
+/// use rustkdb::tick::subscribe;
+///
+/// # async fn doc() -> std::io::Result<()> {
+/// let handle = connect("localhost", 5010, "kdbuser:pass", 200, 0).await?;
+/// let mut updates = subscribe(handle, &["trade"], &["FDP"]).await?;
+/// while let Some(update) = updates.next().await {
+///   let (table, rows) = update?;
+///   println!("{}: {} columns", table, rows.columns().len());
+/// }
+/// # Ok(())}
+/// ```
+pub async fn subscribe(mut handle: Handle, tables: &[&str], syms: &[&str]) -> io::Result<TableUpdates> {
+  let mut snapshots = Vec::new();
+  for table in tables {
+    let reply = handle
+      .send_query(Q::MixedList(vec![
+        Q::Symbol(".u.sub".to_string()),
+        Q::Symbol(table.to_string()),
+        symbol_filter(syms),
+      ]))
+      .await?;
+    collect_snapshots(reply, &mut snapshots);
+  }
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  for snapshot in snapshots {
+    // The receiver side is not live yet, so this cannot fail.
+    let _ = sender.send(Ok(snapshot));
+  }
+  let task = tokio::spawn(async move {
+    loop {
+      match handle.receive_message().await {
+        Ok((MessageType::Async, message)) => {
+          if let Some(update) = parse_upd(message) {
+            if sender.send(Ok(update)).is_err() {
+              break;
+            }
+          }
+        }
+        // Sync traffic is not part of the subscription protocol.
+        Ok(_) => continue,
+        Err(error) => {
+          let disconnected = is_disconnection(&error);
+          if sender.send(Err(error)).is_err() || disconnected {
+            break;
+          }
+        }
+      }
+    }
+  });
+  Ok(TableUpdates { receiver, task })
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Symbol filter argument of `.u.sub`: the empty symbol subscribes to all
+///  symbols, exactly like `` .u.sub[`trade;`] `` in q.
+fn symbol_filter(syms: &[&str]) -> Q {
+  match syms {
+    [] => Q::Symbol(String::new()),
+    [symbol] => Q::Symbol(symbol.to_string()),
+    _ => Q::SymbolList(QList::new(
+      syms.iter().map(|symbol| symbol.to_string()).collect(),
+    )),
+  }
+}
+
+/// Collect `(name, rows)` pairs out of a `.u.sub` reply. The reply is a
+///  `(name; rows)` pair for one table or a list of such pairs; anything
+///  else — commonly a generic null from plants without snapshots — is
+///  ignored.
+fn collect_snapshots(reply: Q, snapshots: &mut Vec<(String, QTable)>) {
+  if let Q::MixedList(items) = reply {
+    if let [Q::Symbol(name), Q::Table(rows)] = items.as_slice() {
+      snapshots.push((name.clone(), rows.clone()));
+    } else {
+      for item in items {
+        collect_snapshots(item, snapshots);
+      }
+    }
+  }
+}
+
+/// Promote the atom columns of a single-row tick to one-element vectors so
+///  further ticks can be appended. Lists pass through unchanged.
+fn atoms_to_columns(data: Q) -> Q {
+  match data {
+    Q::MixedList(columns) => Q::MixedList(columns.into_iter().map(atom_to_column).collect()),
+    other => atom_to_column(other),
+  }
+}
+
+/// Promote an atom to a one-element vector of the matching list type.
+fn atom_to_column(column: Q) -> Q {
+  match column {
+    Q::Bool(value) => Q::BoolList(QList::new(vec![value])),
+    Q::Guid(value) => Q::GuidList(QList::new(vec![value])),
+    Q::Byte(value) => Q::ByteList(QList::new(vec![value])),
+    Q::Short(value) => Q::ShortList(QList::new(vec![value])),
+    Q::Int(value) => Q::IntList(QList::new(vec![value])),
+    Q::Long(value) => Q::LongList(QList::new(vec![value])),
+    Q::Real(value) => Q::RealList(QList::new(vec![value])),
+    Q::Float(value) => Q::FloatList(QList::new(vec![value])),
+    Q::Char(value) => Q::String(value.to_string()),
+    Q::Symbol(value) => Q::SymbolList(QList::new(vec![value])),
+    Q::Timestamp(value) => Q::TimestampList(QList::new(vec![value])),
+    Q::Month(value) => Q::MonthList(QList::new(vec![value])),
+    Q::Date(value) => Q::DateList(QList::new(vec![value])),
+    Q::Datetime(value) => Q::DatetimeList(QList::new(vec![value])),
+    Q::Timespan(value) => Q::TimespanList(QList::new(vec![value])),
+    Q::Minute(value) => Q::MinuteList(QList::new(vec![value])),
+    Q::Second(value) => Q::SecondList(QList::new(vec![value])),
+    Q::Time(value) => Q::TimeList(QList::new(vec![value])),
+    other => other,
+  }
+}
+
+/// Append the columns of `incoming` to `existing` element-wise.
+fn append_column(existing: &mut Q, incoming: Q) -> io::Result<()> {
+  match (existing, incoming) {
+    (Q::BoolList(a), Q::BoolList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::GuidList(a), Q::GuidList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::ByteList(a), Q::ByteList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::ShortList(a), Q::ShortList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::IntList(a), Q::IntList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::LongList(a), Q::LongList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::RealList(a), Q::RealList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::FloatList(a), Q::FloatList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::String(a), Q::String(b)) => a.push_str(&b),
+    (Q::SymbolList(a), Q::SymbolList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::TimestampList(a), Q::TimestampList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::MonthList(a), Q::MonthList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::DateList(a), Q::DateList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::DatetimeList(a), Q::DatetimeList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::TimespanList(a), Q::TimespanList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::MinuteList(a), Q::MinuteList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::SecondList(a), Q::SecondList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::TimeList(a), Q::TimeList(b)) => a.data_mut().extend(b.into_data()),
+    (Q::MixedList(a), Q::MixedList(b)) => {
+      if a.len() != b.len() {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "ticks of one table must have the same number of columns",
+        ));
+      }
+      for (column, incoming) in a.iter_mut().zip(b) {
+        append_column(column, incoming)?;
+      }
+    }
+    _ => {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "ticks of one table must have the same column types",
+      ));
+    }
+  }
+  Ok(())
+}
+
+/// Parse an incoming `(upd; table; rows)` call into a `(name, rows)` pair.
+fn parse_upd(message: Q) -> Option<(String, QTable)> {
+  let Q::MixedList(items) = message else {
+    return None;
+  };
+  let [function, Q::Symbol(name), Q::Table(rows)] = items.as_slice() else {
+    return None;
+  };
+  let is_upd = matches!(function, Q::Symbol(f) if f == "upd")
+    || matches!(function, Q::String(f) if f == "upd");
+  if is_upd {
+    Some((name.clone(), rows.clone()))
+  } else {
+    None
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                          Tests                        //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::connection::connect_stream;
+  use crate::deserialization::deserialize_q;
+  use crate::serialization::{serialize_message, MSG_TYPE_ASYNC, MSG_TYPE_RESPONSE};
+  use futures_util::StreamExt;
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+  /// Rows of a one-column trade table for the given prices.
+  fn trade(prices: Vec<f64>) -> QTable {
+    QTable::new(
+      vec!["price".to_string()],
+      vec![Q::FloatList(QList::new(prices))],
+    )
+    .unwrap()
+  }
+
+  /// Read one complete IPC message from the server side of a duplex pair.
+  async fn read_message<S>(server: &mut S) -> (u8, Q)
+  where
+    S: tokio::io::AsyncRead + Unpin,
+  {
+    let mut header = [0u8; 8];
+    server.read_exact(&mut header).await.unwrap();
+    let size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+    let mut body = vec![0u8; size - 8];
+    server.read_exact(&mut body).await.unwrap();
+    (header[1], deserialize_q(&body, true).unwrap())
+  }
+
+  #[tokio::test]
+  async fn tplog_writer_round_trips_through_the_reader() {
+    let path = std::env::temp_dir().join(format!("rustkdb-tplog-w-{}", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    let upd = |price: f64| {
+      Q::MixedList(vec![
+        Q::Symbol("upd".to_string()),
+        Q::Symbol("trade".to_string()),
+        Q::Table(trade(vec![price])),
+      ])
+    };
+    let mut log = TplogWriter::open(&path).await.unwrap();
+    log.append_upd("trade", Q::Table(trade(vec![100.0]))).await.unwrap();
+    log.sync().await.unwrap();
+    drop(log);
+    // Reopening appends instead of truncating and writes no second header.
+    let mut log = TplogWriter::open(&path).await.unwrap();
+    log.append(&upd(101.0)).await.unwrap();
+    log.sync().await.unwrap();
+    drop(log);
+    let messages: Vec<Q> = TplogReader::open(&path)
+      .await
+      .unwrap()
+      .collect::<io::Result<_>>()
+      .unwrap();
+    assert_eq!(messages, vec![upd(100.0), upd(101.0)]);
+    tokio::fs::remove_file(&path).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn tplog_is_decoded_and_replayed() {
+    use crate::serialization::serialize_q;
+    // Lay out a minimal tplog: the 8 byte header followed by two upd
+    // messages back to back, exactly as q appends them.
+    let upd = |price: f64| {
+      Q::MixedList(vec![
+        Q::Symbol("upd".to_string()),
+        Q::Symbol("trade".to_string()),
+        Q::Table(trade(vec![price])),
+      ])
+    };
+    let mut bytes = vec![0xff, 0x01, 0, 0, 0, 0, 0, 0];
+    serialize_q(&upd(100.0), &mut bytes);
+    serialize_q(&upd(101.0), &mut bytes);
+    let path = std::env::temp_dir().join(format!("rustkdb-tplog-{}", std::process::id()));
+    let path = path.to_str().unwrap().to_string();
+    tokio::fs::write(&path, &bytes).await.unwrap();
+    // The iterator decodes both messages.
+    let messages: Vec<Q> = TplogReader::open(&path)
+      .await
+      .unwrap()
+      .collect::<io::Result<_>>()
+      .unwrap();
+    assert_eq!(messages, vec![upd(100.0), upd(101.0)]);
+    // Replaying re-sends them as async messages.
+    let (client, mut server) = tokio::io::duplex(4096);
+    let server_task = tokio::spawn(async move {
+      let mut byte = [0u8; 1];
+      loop {
+        server.read_exact(&mut byte).await.unwrap();
+        if byte[0] == 0 {
+          break;
+        }
+      }
+      server.write_all(&[3]).await.unwrap();
+      let first = read_message(&mut server).await;
+      let second = read_message(&mut server).await;
+      (first, second)
+    });
+    let mut handle = connect_stream(client, "kdbuser:pass").await.unwrap();
+    let reader = TplogReader::open(&path).await.unwrap();
+    assert_eq!(reader.replay_into(&mut handle).await.unwrap(), 2);
+    let ((first_type, first), (_, second)) = server_task.await.unwrap();
+    assert_eq!(first_type, 0);
+    assert_eq!(first, upd(100.0));
+    assert_eq!(second, upd(101.0));
+    tokio::fs::remove_file(&path).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn publisher_batches_ticks_per_table() {
+    let (client, mut server) = tokio::io::duplex(4096);
+    let server_task = tokio::spawn(async move {
+      let mut byte = [0u8; 1];
+      loop {
+        server.read_exact(&mut byte).await.unwrap();
+        if byte[0] == 0 {
+          break;
+        }
+      }
+      server.write_all(&[3]).await.unwrap();
+      let first = read_message(&mut server).await;
+      let second = read_message(&mut server).await;
+      (first, second)
+    });
+    let mut handle = connect_stream(client, "kdbuser:pass").await.unwrap();
+    let mut publisher = Publisher::new(2);
+    let tick = |price: f64| Q::MixedList(vec![Q::Symbol("FDP".to_string()), Q::Float(price)]);
+    publisher.publish(&mut handle, "trade", tick(100.0)).await.unwrap();
+    publisher.publish(&mut handle, "quote", tick(99.5)).await.unwrap();
+    assert_eq!(publisher.pending_tables(), 2);
+    // The second trade tick completes the batch; quote stays buffered
+    // until the explicit flush.
+    publisher.publish(&mut handle, "trade", tick(101.0)).await.unwrap();
+    publisher.flush(&mut handle).await.unwrap();
+    assert_eq!(publisher.pending_tables(), 0);
+    let (first, second) = server_task.await.unwrap();
+    assert_eq!(first.0, 0);
+    assert_eq!(
+      first.1,
+      Q::MixedList(vec![
+        Q::Symbol(".u.upd".to_string()),
+        Q::Symbol("trade".to_string()),
+        Q::MixedList(vec![
+          Q::SymbolList(QList::new(vec!["FDP".to_string(), "FDP".to_string()])),
+          Q::FloatList(QList::new(vec![100.0, 101.0])),
+        ]),
+      ])
+    );
+    assert_eq!(
+      second.1,
+      Q::MixedList(vec![
+        Q::Symbol(".u.upd".to_string()),
+        Q::Symbol("quote".to_string()),
+        Q::MixedList(vec![
+          Q::SymbolList(QList::new(vec!["FDP".to_string()])),
+          Q::FloatList(QList::new(vec![99.5])),
+        ]),
+      ])
+    );
+  }
+
+  #[tokio::test]
+  async fn snapshot_and_updates_flow_through_the_stream() {
+    let (client, mut server) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+      let mut byte = [0u8; 1];
+      loop {
+        server.read_exact(&mut byte).await.unwrap();
+        if byte[0] == 0 {
+          break;
+        }
+      }
+      server.write_all(&[3]).await.unwrap();
+      // Read the .u.sub call and check its shape.
+      let mut header = [0u8; 8];
+      server.read_exact(&mut header).await.unwrap();
+      let size = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+      let mut body = vec![0u8; size - 8];
+      server.read_exact(&mut body).await.unwrap();
+      let call = deserialize_q(&body, true).unwrap();
+      assert_eq!(
+        call,
+        Q::MixedList(vec![
+          Q::Symbol(".u.sub".to_string()),
+          Q::Symbol("trade".to_string()),
+          Q::Symbol("FDP".to_string()),
+        ])
+      );
+      // Reply with a snapshot, then publish two updates.
+      let snapshot = Q::MixedList(vec![
+        Q::Symbol("trade".to_string()),
+        Q::Table(trade(vec![100.0])),
+      ]);
+      server
+        .write_all(&serialize_message(&snapshot, MSG_TYPE_RESPONSE))
+        .await
+        .unwrap();
+      for price in [101.0, 102.0] {
+        let upd = Q::MixedList(vec![
+          Q::Symbol("upd".to_string()),
+          Q::Symbol("trade".to_string()),
+          Q::Table(trade(vec![price])),
+        ]);
+        server
+          .write_all(&serialize_message(&upd, MSG_TYPE_ASYNC))
+          .await
+          .unwrap();
+      }
+    });
+    let handle = connect_stream(client, "kdbuser:pass").await.unwrap();
+    let mut updates = subscribe(handle, &["trade"], &["FDP"]).await.unwrap();
+    for expected in [100.0, 101.0, 102.0] {
+      let (name, rows) = updates.next().await.unwrap().unwrap();
+      assert_eq!(name, "trade");
+      assert_eq!(rows, trade(vec![expected]));
+    }
+    // The publisher dropped its end: the terminal error is yielded, then None.
+    assert!(updates.next().await.unwrap().is_err());
+    assert!(updates.next().await.is_none());
+  }
+}
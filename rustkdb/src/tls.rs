@@ -0,0 +1,560 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! TLS configuration for [`connect_tls`](crate::connection::connect_tls) and
+//! [`ConnectOptions`](crate::connection::ConnectOptions).
+//!
+//! Two backends are available behind mutually exclusive cargo features:
+//! - `tls-native` (default): OpenSSL/SChannel/Secure Transport via `native-tls`.
+//! - `tls-rustls`: pure Rust TLS via `rustls`, for targets which cannot ship
+//!   OpenSSL such as static musl builds.
+//!
+//! The connection surface is identical for both backends.
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+use std::io;
+
+#[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+use sha2::{Digest, Sha256};
+
+#[cfg(all(feature = "tls-rustls", not(feature = "tls-native")))]
+use std::sync::Arc;
+
+#[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+use tokio::net::TcpStream;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Global Variables                   //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// TLS stream type of the selected backend.
+#[cfg(feature = "tls-native")]
+pub(crate) type TlsStream = tokio_native_tls::TlsStream<TcpStream>;
+/// TLS stream type of the selected backend.
+#[cfg(all(feature = "tls-rustls", not(feature = "tls-native")))]
+pub(crate) type TlsStream = tokio_rustls::client::TlsStream<TcpStream>;
+
+/// Server-side TLS stream type of the selected backend.
+#[cfg(feature = "tls-native")]
+pub(crate) type TlsServerStream = tokio_native_tls::TlsStream<TcpStream>;
+/// Server-side TLS stream type of the selected backend.
+#[cfg(all(feature = "tls-rustls", not(feature = "tls-native")))]
+pub(crate) type TlsServerStream = tokio_rustls::server::TlsStream<TcpStream>;
+
+/// Server-side TLS acceptor of the selected backend.
+#[cfg(feature = "tls-native")]
+pub(crate) type TlsAcceptor = tokio_native_tls::TlsAcceptor;
+/// Server-side TLS acceptor of the selected backend.
+#[cfg(all(feature = "tls-rustls", not(feature = "tls-native")))]
+pub(crate) type TlsAcceptor = tokio_rustls::TlsAcceptor;
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% TlsIdentity %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Client certificate and private key presented to kdb+ servers requiring
+///  mutual TLS.
+#[derive(Clone, Debug)]
+pub enum TlsIdentity {
+  /// PKCS#12 archive (`.p12`/`.pfx`) together with its password.
+  /// # Note
+  /// Only supported by the `tls-native` backend.
+  Pkcs12 {
+    /// DER encoded archive bytes.
+    der: Vec<u8>,
+    /// Password protecting the archive.
+    password: String,
+  },
+  /// PEM encoded certificate chain and PKCS#8 private key.
+  Pem {
+    /// PEM encoded certificate chain.
+    certificate: Vec<u8>,
+    /// PEM encoded PKCS#8 private key.
+    key: Vec<u8>,
+  },
+}
+
+//%% TlsTrust %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// How the server certificate is verified.
+#[derive(Clone, Debug, Default)]
+pub enum TlsTrust {
+  /// Verify against the default trust store, i.e. the system store for
+  ///  `tls-native` and the bundled Mozilla roots for `tls-rustls`.
+  #[default]
+  SystemRoots,
+  /// Verify against a custom PEM encoded CA bundle instead of the default
+  ///  trust store. The bundle may contain several certificates.
+  CustomRoots {
+    /// PEM encoded CA bundle.
+    bundle: Vec<u8>,
+  },
+  /// Accept only certificates whose SHA-256 fingerprint over the DER
+  ///  encoding matches one of the given pins. Chain verification is skipped
+  ///  entirely, so self-signed certificates can be pinned.
+  PinnedCertificates {
+    /// SHA-256 fingerprints of accepted certificates.
+    fingerprints: Vec<[u8; 32]>,
+  },
+}
+
+//%% TlsConfig %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Minimum accepted TLS protocol version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsVersion {
+  /// TLS 1.2.
+  Tls12,
+  /// TLS 1.3.
+  Tls13,
+}
+
+/// Full TLS configuration of a connection.
+/// # Example
+/// ```
+/// use rustkdb::tls::{TlsConfig, TlsVersion};
+///
+/// let config = TlsConfig::new()
+///   .sni_hostname("gateway.internal")
+///   .min_version(TlsVersion::Tls12);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TlsConfig {
+  /// Client identity presented to servers requiring mutual TLS.
+  pub(crate) identity: Option<TlsIdentity>,
+  /// How the server certificate is verified.
+  pub(crate) trust: TlsTrust,
+  /// Hostname sent in the SNI extension and checked against the server
+  ///  certificate, overriding the hostname used for the TCP connection.
+  pub(crate) sni_hostname: Option<String>,
+  /// Minimum accepted TLS protocol version.
+  pub(crate) min_version: Option<TlsVersion>,
+  /// ALPN protocols offered to the server, in preference order.
+  pub(crate) alpn_protocols: Vec<Vec<u8>>,
+  /// Restrict the cipher suites offered to the server, e.g.
+  ///  `TLS13_AES_128_GCM_SHA256`. Only supported by the `tls-rustls` backend.
+  pub(crate) cipher_suites: Vec<String>,
+}
+
+impl TlsConfig {
+  /// Start from the backend defaults, i.e. default trust store, no client
+  ///  identity and no protocol restrictions.
+  pub fn new() -> Self {
+    TlsConfig::default()
+  }
+
+  /// Present the given client identity to servers requiring mutual TLS.
+  pub fn identity(mut self, identity: TlsIdentity) -> Self {
+    self.identity = Some(identity);
+    self
+  }
+
+  /// Set how the server certificate is verified.
+  pub fn trust(mut self, trust: TlsTrust) -> Self {
+    self.trust = trust;
+    self
+  }
+
+  /// Override the hostname sent in the SNI extension and checked against
+  ///  the server certificate.
+  pub fn sni_hostname(mut self, hostname: &str) -> Self {
+    self.sni_hostname = Some(hostname.to_string());
+    self
+  }
+
+  /// Set the minimum accepted TLS protocol version.
+  pub fn min_version(mut self, version: TlsVersion) -> Self {
+    self.min_version = Some(version);
+    self
+  }
+
+  /// Offer the given ALPN protocols to the server, in preference order.
+  pub fn alpn_protocols(mut self, protocols: &[&[u8]]) -> Self {
+    self.alpn_protocols = protocols.iter().map(|protocol| protocol.to_vec()).collect();
+    self
+  }
+
+  /// Restrict the cipher suites offered to the server, e.g.
+  ///  `TLS13_AES_128_GCM_SHA256`.
+  /// # Note
+  /// Only supported by the `tls-rustls` backend; `tls-native` rejects a
+  ///  non-empty restriction at connection time.
+  pub fn cipher_suites(mut self, suites: &[&str]) -> Self {
+    self.cipher_suites = suites.iter().map(|suite| suite.to_string()).collect();
+    self
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                 Native TLS Backend                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(feature = "tls-native")]
+mod backend {
+  use super::*;
+
+  /// Split a PEM bundle into its individual certificate blocks.
+  fn split_pem_bundle(bundle: &[u8]) -> Vec<&[u8]> {
+    const BEGIN: &[u8] = b"-----BEGIN CERTIFICATE-----";
+    const END: &[u8] = b"-----END CERTIFICATE-----";
+    let mut blocks = Vec::new();
+    let mut rest = bundle;
+    while let Some(start) = rest
+      .windows(BEGIN.len())
+      .position(|window| window == BEGIN)
+    {
+      let Some(end) = rest[start..]
+        .windows(END.len())
+        .position(|window| window == END)
+      else {
+        break;
+      };
+      blocks.push(&rest[start..start + end + END.len()]);
+      rest = &rest[start + end + END.len()..];
+    }
+    blocks
+  }
+
+  /// Build a TLS connector applying the given configuration.
+  fn build_connector(config: &TlsConfig) -> io::Result<tokio_native_tls::TlsConnector> {
+    let mut builder = native_tls::TlsConnector::builder();
+    if !config.cipher_suites.is_empty() {
+      return Err(io::Error::other(
+        "cipher suite restriction is not supported by the tls-native backend; use tls-rustls",
+      ));
+    }
+    if let Some(version) = config.min_version {
+      builder.min_protocol_version(Some(match version {
+        TlsVersion::Tls12 => native_tls::Protocol::Tlsv12,
+        // native-tls has no TLS 1.3 marker, and silently accepting TLS 1.2
+        //  would defeat the point of the restriction.
+        TlsVersion::Tls13 => {
+          return Err(io::Error::other(
+            "a TLS 1.3 minimum is not supported by the tls-native backend; use tls-rustls",
+          ))
+        }
+      }));
+    }
+    if !config.alpn_protocols.is_empty() {
+      let protocols: Vec<&str> = config
+        .alpn_protocols
+        .iter()
+        .map(|protocol| {
+          std::str::from_utf8(protocol)
+            .map_err(|_| io::Error::other("ALPN protocols must be UTF-8 for tls-native"))
+        })
+        .collect::<io::Result<Vec<&str>>>()?;
+      builder.request_alpns(&protocols);
+    }
+    if let Some(identity) = &config.identity {
+      let identity = match identity {
+        TlsIdentity::Pkcs12 { der, password } => {
+          native_tls::Identity::from_pkcs12(der, password).map_err(io::Error::other)?
+        }
+        TlsIdentity::Pem { certificate, key } => {
+          native_tls::Identity::from_pkcs8(certificate, key).map_err(io::Error::other)?
+        }
+      };
+      builder.identity(identity);
+    }
+    match &config.trust {
+      TlsTrust::SystemRoots => {}
+      TlsTrust::CustomRoots { bundle } => {
+        builder.disable_built_in_roots(true);
+        for block in split_pem_bundle(bundle) {
+          let certificate =
+            native_tls::Certificate::from_pem(block).map_err(io::Error::other)?;
+          builder.add_root_certificate(certificate);
+        }
+      }
+      TlsTrust::PinnedCertificates { .. } => {
+        // Chain verification is replaced by the fingerprint check performed
+        // right after the TLS handshake.
+        builder.danger_accept_invalid_certs(true);
+        builder.danger_accept_invalid_hostnames(true);
+      }
+    }
+    let connector = builder.build().map_err(io::Error::other)?;
+    Ok(tokio_native_tls::TlsConnector::from(connector))
+  }
+
+  /// Check the peer certificate against pinned fingerprints. Does nothing
+  ///  for other trust settings.
+  fn verify_pinned(stream: &TlsStream, trust: &TlsTrust) -> io::Result<()> {
+    let TlsTrust::PinnedCertificates { fingerprints } = trust else {
+      return Ok(());
+    };
+    let certificate = stream
+      .get_ref()
+      .peer_certificate()
+      .map_err(io::Error::other)?
+      .ok_or_else(|| io::Error::other("server presented no certificate"))?;
+    let der = certificate.to_der().map_err(io::Error::other)?;
+    if fingerprint_matches(&der, fingerprints) {
+      Ok(())
+    } else {
+      Err(io::Error::other(
+        "server certificate does not match any pinned fingerprint",
+      ))
+    }
+  }
+
+  /// Run the TLS handshake on top of an established TCP connection.
+  pub(crate) async fn connect_tls_stream(
+    host: &str,
+    tcp: TcpStream,
+    config: &TlsConfig,
+  ) -> io::Result<TlsStream> {
+    let connector = build_connector(config)?;
+    let domain = config.sni_hostname.as_deref().unwrap_or(host);
+    let stream = connector
+      .connect(domain, tcp)
+      .await
+      .map_err(|error| io::Error::other(format!("TLS negotiation failed: {}", error)))?;
+    verify_pinned(&stream, &config.trust)?;
+    Ok(stream)
+  }
+
+  /// Build a server-side acceptor presenting the given identity.
+  pub(crate) fn build_acceptor(identity: &TlsIdentity) -> io::Result<TlsAcceptor> {
+    let native = match identity {
+      TlsIdentity::Pkcs12 { der, password } => {
+        native_tls::Identity::from_pkcs12(der, password)
+      }
+      TlsIdentity::Pem { certificate, key } => {
+        native_tls::Identity::from_pkcs8(certificate, key)
+      }
+    }
+    .map_err(io::Error::other)?;
+    let acceptor = native_tls::TlsAcceptor::new(native).map_err(io::Error::other)?;
+    Ok(tokio_native_tls::TlsAcceptor::from(acceptor))
+  }
+
+  /// Run the server-side TLS handshake on top of an accepted TCP connection.
+  pub(crate) async fn accept_tls_stream(
+    acceptor: &TlsAcceptor,
+    tcp: TcpStream,
+  ) -> io::Result<TlsServerStream> {
+    acceptor
+      .accept(tcp)
+      .await
+      .map_err(|error| io::Error::other(format!("TLS negotiation failed: {}", error)))
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Rustls Backend                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+#[cfg(all(feature = "tls-rustls", not(feature = "tls-native")))]
+mod backend {
+  use super::*;
+
+  use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+  };
+  use tokio_rustls::rustls::crypto::ring as crypto_provider;
+  use tokio_rustls::rustls::pki_types::{
+    CertificateDer, ServerName, UnixTime,
+  };
+  use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, RootCertStore};
+
+  /// Certificate verifier accepting exactly the pinned fingerprints.
+  #[derive(Debug)]
+  struct PinnedVerifier {
+    /// SHA-256 fingerprints of accepted certificates.
+    fingerprints: Vec<[u8; 32]>,
+  }
+
+  impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+      &self,
+      end_entity: &CertificateDer<'_>,
+      _intermediates: &[CertificateDer<'_>],
+      _server_name: &ServerName<'_>,
+      _ocsp_response: &[u8],
+      _now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+      if fingerprint_matches(end_entity.as_ref(), &self.fingerprints) {
+        Ok(ServerCertVerified::assertion())
+      } else {
+        Err(tokio_rustls::rustls::Error::General(
+          "server certificate does not match any pinned fingerprint".to_string(),
+        ))
+      }
+    }
+
+    fn verify_tls12_signature(
+      &self,
+      _message: &[u8],
+      _cert: &CertificateDer<'_>,
+      _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+      // The certificate itself is pinned; the signature adds nothing.
+      Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+      &self,
+      _message: &[u8],
+      _cert: &CertificateDer<'_>,
+      _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+      Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<tokio_rustls::rustls::SignatureScheme> {
+      crypto_provider::default_provider()
+        .signature_verification_algorithms
+        .supported_schemes()
+    }
+  }
+
+  /// Parse a PEM bundle into DER certificates.
+  fn parse_pem_certificates(bundle: &[u8]) -> io::Result<Vec<CertificateDer<'static>>> {
+    rustls_pemfile::certs(&mut &bundle[..]).collect::<Result<Vec<_>, _>>()
+  }
+
+  /// Build a rustls client configuration from the given configuration.
+  fn build_config(config: &TlsConfig) -> io::Result<ClientConfig> {
+    let mut provider = crypto_provider::default_provider();
+    if !config.cipher_suites.is_empty() {
+      provider.cipher_suites.retain(|suite| {
+        let name = format!("{:?}", suite.suite());
+        config
+          .cipher_suites
+          .iter()
+          .any(|wanted| wanted.eq_ignore_ascii_case(&name))
+      });
+      if provider.cipher_suites.is_empty() {
+        return Err(io::Error::other("no supported cipher suite matches the restriction"));
+      }
+    }
+    let versions: &[&tokio_rustls::rustls::SupportedProtocolVersion] =
+      match config.min_version {
+        None | Some(TlsVersion::Tls12) => tokio_rustls::rustls::ALL_VERSIONS,
+        Some(TlsVersion::Tls13) => &[&tokio_rustls::rustls::version::TLS13],
+      };
+    let builder = ClientConfig::builder_with_provider(Arc::new(provider))
+      .with_protocol_versions(versions)
+      .map_err(io::Error::other)?;
+    let builder = match &config.trust {
+      TlsTrust::SystemRoots => {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        builder.with_root_certificates(roots)
+      }
+      TlsTrust::CustomRoots { bundle } => {
+        let mut roots = RootCertStore::empty();
+        for certificate in parse_pem_certificates(bundle)? {
+          roots.add(certificate).map_err(io::Error::other)?;
+        }
+        builder.with_root_certificates(roots)
+      }
+      TlsTrust::PinnedCertificates { fingerprints } => builder
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedVerifier {
+          fingerprints: fingerprints.clone(),
+        })),
+    };
+    let mut client_config = match &config.identity {
+      None => builder.with_no_client_auth(),
+      Some(TlsIdentity::Pem { certificate, key }) => {
+        let certificates = parse_pem_certificates(certificate)?;
+        let key = rustls_pemfile::private_key(&mut &key[..])?
+          .ok_or_else(|| io::Error::other("no private key found in PEM input"))?;
+        builder
+          .with_client_auth_cert(certificates, key)
+          .map_err(io::Error::other)?
+      }
+      Some(TlsIdentity::Pkcs12 { .. }) => {
+        return Err(io::Error::other(
+          "PKCS#12 identities are not supported by the tls-rustls backend; use TlsIdentity::Pem",
+        ))
+      }
+    };
+    client_config.alpn_protocols = config.alpn_protocols.clone();
+    Ok(client_config)
+  }
+
+  /// Run the TLS handshake on top of an established TCP connection.
+  pub(crate) async fn connect_tls_stream(
+    host: &str,
+    tcp: TcpStream,
+    config: &TlsConfig,
+  ) -> io::Result<TlsStream> {
+    let client_config = build_config(config)?;
+    let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+    let domain = config.sni_hostname.as_deref().unwrap_or(host);
+    let server_name = ServerName::try_from(domain.to_string())
+      .map_err(|_| io::Error::other(format!("invalid server name: {}", domain)))?;
+    connector
+      .connect(server_name, tcp)
+      .await
+      .map_err(|error| {
+        io::Error::new(
+          error.kind(),
+          format!("TLS negotiation failed: {}", error),
+        )
+      })
+  }
+
+  /// Build a server-side acceptor presenting the given identity.
+  pub(crate) fn build_acceptor(identity: &TlsIdentity) -> io::Result<TlsAcceptor> {
+    let (certificate, key) = match identity {
+      TlsIdentity::Pem { certificate, key } => (certificate, key),
+      TlsIdentity::Pkcs12 { .. } => {
+        return Err(io::Error::other(
+          "PKCS#12 identities are not supported by the tls-rustls backend; use TlsIdentity::Pem",
+        ))
+      }
+    };
+    let certificates = parse_pem_certificates(certificate)?;
+    let key = rustls_pemfile::private_key(&mut &key[..])?
+      .ok_or_else(|| io::Error::other("no private key found in PEM input"))?;
+    let config = tokio_rustls::rustls::ServerConfig::builder_with_provider(Arc::new(
+      crypto_provider::default_provider(),
+    ))
+    .with_protocol_versions(tokio_rustls::rustls::ALL_VERSIONS)
+    .map_err(io::Error::other)?
+    .with_no_client_auth()
+    .with_single_cert(certificates, key)
+    .map_err(io::Error::other)?;
+    Ok(tokio_rustls::TlsAcceptor::from(Arc::new(config)))
+  }
+
+  /// Run the server-side TLS handshake on top of an accepted TCP connection.
+  pub(crate) async fn accept_tls_stream(
+    acceptor: &TlsAcceptor,
+    tcp: TcpStream,
+  ) -> io::Result<TlsServerStream> {
+    acceptor.accept(tcp).await.map_err(|error| {
+      io::Error::new(
+        error.kind(),
+        format!("TLS negotiation failed: {}", error),
+      )
+    })
+  }
+}
+
+#[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+pub(crate) use backend::{accept_tls_stream, build_acceptor, connect_tls_stream};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                    Private Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// `true` if the SHA-256 fingerprint of the DER encoded certificate matches
+///  one of the pins.
+#[cfg(any(feature = "tls-native", feature = "tls-rustls"))]
+fn fingerprint_matches(der: &[u8], fingerprints: &[[u8; 32]]) -> bool {
+  let fingerprint: [u8; 32] = Sha256::digest(der).into();
+  fingerprints.contains(&fingerprint)
+}
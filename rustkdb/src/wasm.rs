@@ -0,0 +1,282 @@
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                        Preamble                       //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//! Browser WebSocket client for `wasm32-unknown-unknown`.
+//!
+//! Dashboards compiled to WASM cannot open TCP sockets, but q serves
+//! WebSocket clients through `.z.ws` with the ordinary IPC serialization
+//! inside binary frames. [`connect_ws`] wraps the browser `WebSocket`
+//! object into a [`WasmHandle`] with the familiar query API, so the same
+//! [`Q`] values work in the browser and on the server.
+//!
+//! Enabled with the `wasm` cargo feature; the module only exists on the
+//! `wasm32` architecture.
+//!
+//! # Example
+//! ```ignore
+//! use rustkdb::qtype::Q;
+//! use rustkdb::wasm::connect_ws;
+//!
+//! async fn dashboard() -> std::io::Result<()> {
+//!   let mut handle = connect_ws("ws://localhost:5000").await?;
+//!   let result = handle.send_string_query("6*7").await?;
+//!   assert_eq!(result, Q::Long(42));
+//!   Ok(())
+//! }
+//! ```
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                     Load Libraries                    //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+use crate::deserialization::{decompress, deserialize_q};
+use crate::qtype::Q;
+use crate::serialization::{
+  serialize_message, serialize_string_query, MSG_TYPE_ASYNC, MSG_TYPE_SYNC,
+};
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                       Structures                      //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+//%% WasmHandle %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// State shared between the browser socket callbacks and the handle.
+struct SocketState {
+  /// `true` once the `open` event fired.
+  open: bool,
+  /// Reason the socket is unusable, set by the `close` and `error` events.
+  closed: Option<String>,
+  /// Binary frames received and not yet consumed.
+  frames: VecDeque<Vec<u8>>,
+  /// Task waiting for the next event.
+  waker: Option<Waker>,
+}
+
+impl SocketState {
+  /// Wake the task waiting on the socket, if any.
+  fn wake(&mut self) {
+    if let Some(waker) = self.waker.take() {
+      waker.wake();
+    }
+  }
+}
+
+/// Handle to a q/kdb+ process over a browser WebSocket (`.z.ws`),
+///  obtained from [`connect_ws`].
+pub struct WasmHandle {
+  /// The browser socket.
+  socket: WebSocket,
+  /// State shared with the socket callbacks.
+  state: Rc<RefCell<SocketState>>,
+  /// Keeps the `open` callback alive for the lifetime of the socket.
+  _on_open: Closure<dyn FnMut()>,
+  /// Keeps the `message` callback alive for the lifetime of the socket.
+  _on_message: Closure<dyn FnMut(MessageEvent)>,
+  /// Keeps the `error` callback alive for the lifetime of the socket.
+  _on_error: Closure<dyn FnMut()>,
+  /// Keeps the `close` callback alive for the lifetime of the socket.
+  _on_close: Closure<dyn FnMut()>,
+}
+
+impl WasmHandle {
+  /// Send a string query synchronously and wait for the result.
+  /// # Parameters
+  /// - `query`: Query in the form of string, e.g. `"6*7"`.
+  pub async fn send_string_query(&mut self, query: &str) -> io::Result<Q> {
+    let message = serialize_string_query(query, MSG_TYPE_SYNC);
+    self.send_frame(&message)?;
+    self.receive_response().await
+  }
+
+  /// Send a string query asynchronously, i.e. without waiting for a result.
+  pub async fn send_string_query_async(&mut self, query: &str) -> io::Result<()> {
+    let message = serialize_string_query(query, MSG_TYPE_ASYNC);
+    self.send_frame(&message)
+  }
+
+  /// Send a q object synchronously and wait for the result.
+  pub async fn send_query(&mut self, query: Q) -> io::Result<Q> {
+    let message = serialize_message(&query, MSG_TYPE_SYNC);
+    self.send_frame(&message)?;
+    self.receive_response().await
+  }
+
+  /// Send a q object asynchronously, i.e. without waiting for a result.
+  pub async fn send_query_async(&mut self, query: Q) -> io::Result<()> {
+    let message = serialize_message(&query, MSG_TYPE_ASYNC);
+    self.send_frame(&message)
+  }
+
+  /// Close the WebSocket.
+  pub fn close(self) {
+    let _ = self.socket.close();
+  }
+
+  /// Send one binary frame.
+  fn send_frame(&self, message: &[u8]) -> io::Result<()> {
+    if let Some(reason) = &self.state.borrow().closed {
+      return Err(io::Error::new(io::ErrorKind::NotConnected, reason.clone()));
+    }
+    self
+      .socket
+      .send_with_u8_array(message)
+      .map_err(|_| io::Error::other("websocket send failed"))
+  }
+
+  /// Wait for the next binary frame and deserialize the contained message,
+  ///  mirroring the native WebSocket handle.
+  async fn receive_response(&mut self) -> io::Result<Q> {
+    let bytes = NextFrame {
+      state: Rc::clone(&self.state),
+    }
+    .await?;
+    if bytes.len() < 8 {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "broken message: too short",
+      ));
+    }
+    let little_endian = bytes[0] == 1;
+    let mut body = bytes[8..].to_vec();
+    if bytes[2] == 1 {
+      body = decompress(&body, little_endian)?;
+    }
+    deserialize_q(&body, little_endian)
+  }
+}
+
+//%% Futures %%//vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv/
+
+/// Future resolving once the `open` event fired.
+struct WaitOpen {
+  /// State shared with the socket callbacks.
+  state: Rc<RefCell<SocketState>>,
+}
+
+impl Future for WaitOpen {
+  type Output = io::Result<()>;
+
+  fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut state = self.state.borrow_mut();
+    if let Some(reason) = &state.closed {
+      return Poll::Ready(Err(io::Error::new(
+        io::ErrorKind::NotConnected,
+        reason.clone(),
+      )));
+    }
+    if state.open {
+      return Poll::Ready(Ok(()));
+    }
+    state.waker = Some(context.waker().clone());
+    Poll::Pending
+  }
+}
+
+/// Future resolving with the next binary frame.
+struct NextFrame {
+  /// State shared with the socket callbacks.
+  state: Rc<RefCell<SocketState>>,
+}
+
+impl Future for NextFrame {
+  type Output = io::Result<Vec<u8>>;
+
+  fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut state = self.state.borrow_mut();
+    if let Some(frame) = state.frames.pop_front() {
+      return Poll::Ready(Ok(frame));
+    }
+    if let Some(reason) = &state.closed {
+      return Poll::Ready(Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        reason.clone(),
+      )));
+    }
+    state.waker = Some(context.waker().clone());
+    Poll::Pending
+  }
+}
+
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+//                   Exported Functions                  //
+//+++++++++++++++++++++++++++++++++++++++++++++++++++++++//
+
+/// Connect to a q/kdb+ process through the browser WebSocket API.
+/// # Parameters
+/// - `url`: WebSocket URL of the target q process, e.g.
+///   `"ws://localhost:5000"`.
+pub async fn connect_ws(url: &str) -> io::Result<WasmHandle> {
+  let socket = WebSocket::new(url)
+    .map_err(|_| io::Error::other(format!("cannot open a websocket to {}", url)))?;
+  socket.set_binary_type(BinaryType::Arraybuffer);
+  let state = Rc::new(RefCell::new(SocketState {
+    open: false,
+    closed: None,
+    frames: VecDeque::new(),
+    waker: None,
+  }));
+  let on_open = {
+    let state = Rc::clone(&state);
+    Closure::<dyn FnMut()>::new(move || {
+      let mut state = state.borrow_mut();
+      state.open = true;
+      state.wake();
+    })
+  };
+  socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+  let on_message = {
+    let state = Rc::clone(&state);
+    Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+      if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+        let mut state = state.borrow_mut();
+        state.frames.push_back(js_sys::Uint8Array::new(&buffer).to_vec());
+        state.wake();
+      }
+    })
+  };
+  socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+  let on_error = {
+    let state = Rc::clone(&state);
+    Closure::<dyn FnMut()>::new(move || {
+      let mut state = state.borrow_mut();
+      state.closed.get_or_insert_with(|| "websocket error".to_string());
+      state.wake();
+    })
+  };
+  socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+  let on_close = {
+    let state = Rc::clone(&state);
+    Closure::<dyn FnMut()>::new(move || {
+      let mut state = state.borrow_mut();
+      state.closed.get_or_insert_with(|| "websocket closed".to_string());
+      state.wake();
+    })
+  };
+  socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+  WaitOpen {
+    state: Rc::clone(&state),
+  }
+  .await?;
+  Ok(WasmHandle {
+    socket,
+    state,
+    _on_open: on_open,
+    _on_message: on_message,
+    _on_error: on_error,
+    _on_close: on_close,
+  })
+}